@@ -9,8 +9,8 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 	let fn_name = methods.iter().map(|method| &method.name).collect::<Vec<_>>();
 
 	// This is a nested vector of all the arguments for each of the functions in `fn_name`. It does
-	// not include the `self` or `caller: T::AccountId` parameter, which we always assume are the
-	// first two parameters to these calls.
+	// not include the `self` (or `runtime`) or `caller: T::AccountId` parameter, which we always
+	// assume are the first two parameters to these calls.
 	let args_name = methods
 		.iter()
 		.map(|method| method.args.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>())
@@ -23,8 +23,44 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 		.map(|method| method.args.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>())
 		.collect::<Vec<_>>();
 
+	// The call to make for each function. Functions taking `&mut self` are called on the pallet,
+	// which is borrowed from the runtime with `AsMut`.
+	let fn_call = methods
+		.iter()
+		.zip(&args_name)
+		.map(|(method, args_name)| {
+			let name = &method.name;
+			if method.takes_runtime {
+				quote!(#pallet_struct::<T>::#name(runtime, caller, #( #args_name ),*))
+			} else {
+				quote!(AsMut::<#pallet_struct<T>>::as_mut(runtime).#name(caller, #( #args_name ),*))
+			}
+		})
+		.collect::<Vec<_>>();
+
+	// When some function needs the whole runtime, calls can only be dispatched on `T::Runtime`,
+	// which the pallet's `Config` must bound with `AsMut<Pallet<Self>>`. Otherwise any runtime
+	// which contains the pallet will do.
+	let dispatch_sig = if methods.iter().any(|method| method.takes_runtime) {
+		quote! {
+			pub fn dispatch(
+				self,
+				runtime: &mut T::Runtime,
+				caller: T::AccountId,
+			) -> crate::support::DispatchResult
+		}
+	} else {
+		quote! {
+			pub fn dispatch<R: AsMut<#pallet_struct<T>>>(
+				self,
+				runtime: &mut R,
+				caller: T::AccountId,
+			) -> crate::support::DispatchResult
+		}
+	};
+
 	// This quote block creates an `enum Call` which contains all the calls exposed by our pallet,
-	// and the `Dispatch` trait logic to route a `caller` to access those functions.
+	// and the dispatch logic to route a `caller` to access those functions.
 	let dispatch_impl = quote! {
 		// The callable functions exposed by this pallet.
 		//
@@ -38,19 +74,13 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 
 		// Dispatch logic at the pallet level, mapping each of the items in the `Call` enum to the
 		// appropriate function call with all arguments, including the `caller`.
-		impl<T: Config> crate::support::Dispatch for #pallet_struct<T> {
-			type Caller = T::AccountId;
-			type Call = Call<T>;
-
-			fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> crate::support::DispatchResult {
-				match call {
+		impl<T: Config> Call<T> {
+			#dispatch_sig {
+				match self {
 					#(
 						Call::#fn_name { #( #args_name ),* } => {
-							self.#fn_name(
-								// Note that we assume the first argument of every call is the `caller`.
-								caller,
-								#( #args_name ),*
-							)?;
+							// Note that we assume the first argument of every call is the `caller`.
+							#fn_call?;
 						},
 					)*
 				}
@@ -59,6 +89,129 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 		}
 	};
 
+	// All the argument types across all calls, used to bound the generated trait impls. This keeps
+	// the impls generic over `T` while only requiring the traits from the types we actually use.
+	let all_args_type = args_type.iter().flatten().collect::<Vec<_>>();
+
+	// The argument names as strings, used as the field names in the JSON encoding.
+	let args_str = args_name
+		.iter()
+		.map(|names| names.iter().map(|name| name.to_string()).collect::<Vec<_>>())
+		.collect::<Vec<_>>();
+	let fn_str = fn_name.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+
+	// The weight of each call, falling back to the default weight.
+	let weight = methods
+		.iter()
+		.map(|method| match &method.weight {
+			Some(weight) => quote!(#weight),
+			None => quote!(crate::support::DEFAULT_WEIGHT),
+		})
+		.collect::<Vec<_>>();
+
+	// The argument types as strings, e.g. `T::AccountId`, exposed in the metadata of the calls.
+	let args_type_str = args_type
+		.iter()
+		.map(|types| {
+			types
+				.iter()
+				.map(|type_| quote!(#type_).to_string().replace(' ', ""))
+				.collect::<Vec<_>>()
+		})
+		.collect::<Vec<_>>();
+
+	// This quote block implements `Clone` and the JSON encoding for the `Call` enum, so calls can
+	// be stored in blocks and sent over RPC. A call is encoded as `{"fn_name": {"arg": value}}`.
+	let encode_impl = quote! {
+		impl<T: Config> Clone for Call<T>
+		where
+			#( #all_args_type: Clone ),*
+		{
+			fn clone(&self) -> Self {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => Call::#fn_name {
+							#( #args_name: #args_name.clone() ),*
+						},
+					)*
+				}
+			}
+		}
+
+		impl<T: Config> Call<T> {
+			/// The weight of this call, which is used to compute its fee.
+			// The arguments are in scope of the weight expressions, which may not use them.
+			#[allow(unused_variables)]
+			pub fn weight(&self) -> crate::support::Weight {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => #weight,
+					)*
+				}
+			}
+
+			/// Describes all the calls exposed by this pallet.
+			pub fn metadata() -> Vec<crate::support::CallMetadata> {
+				vec![
+					#(
+						crate::support::CallMetadata {
+							name: #fn_str,
+							args: vec![
+								#(
+									crate::support::ArgMetadata {
+										name: #args_str,
+										type_name: #args_type_str,
+									}
+								),*
+							],
+						},
+					)*
+				]
+			}
+		}
+
+		impl<T: Config> crate::json::ToJson for Call<T>
+		where
+			#( #all_args_type: crate::json::ToJson ),*
+		{
+			fn to_json(&self) -> crate::json::Value {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => crate::json::Value::object([(
+							#fn_str,
+							crate::json::Value::object([
+								#( (#args_str, crate::json::ToJson::to_json(#args_name)) ),*
+							]),
+						)]),
+					)*
+				}
+			}
+		}
+
+		impl<T: Config> crate::json::FromJson for Call<T>
+		where
+			#( #all_args_type: crate::json::FromJson ),*
+		{
+			fn from_json(value: &crate::json::Value) -> Result<Self, &'static str> {
+				let (name, args) = value.as_variant()?;
+				match name {
+					#(
+						#fn_str => Ok(Call::#fn_name {
+							#(
+								#args_name: crate::json::FromJson::from_json(args.field(#args_str)?)?
+							),*
+						}),
+					)*
+					_ => Err("Unknown call"),
+				}
+			}
+		}
+	};
+
 	// Return the generated code.
-	dispatch_impl.into()
+	quote! {
+		#dispatch_impl
+		#encode_impl
+	}
+	.into()
 }
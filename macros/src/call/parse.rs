@@ -5,6 +5,7 @@ use syn::spanned::Spanned;
 mod keyword {
 	syn::custom_keyword!(T);
 	syn::custom_keyword!(AccountId);
+	syn::custom_keyword!(Runtime);
 }
 
 /// This object will collect all the information we need to keep while parsing the callable
@@ -23,8 +24,13 @@ pub struct CallDef {
 pub struct CallVariantDef {
 	/// The function name.
 	pub name: syn::Ident,
+	/// Whether the function takes `runtime: &mut T::Runtime` instead of `&mut self`, so it can
+	/// access other pallets.
+	pub takes_runtime: bool,
 	/// Information on args of the function: `(name, type)`.
 	pub args: Vec<(syn::Ident, Box<syn::Type>)>,
+	/// The weight of the call, given with `#[weight(...)]`, if any.
+	pub weight: Option<syn::Expr>,
 }
 
 impl CallDef {
@@ -50,14 +56,19 @@ impl CallDef {
 				// Here is where we will store all the args for each callable functions.
 				let mut args = vec![];
 
-				// First argument should be some variant of `self`.
-				match method.sig.inputs.first() {
-					Some(syn::FnArg::Receiver(_)) => {},
-					_ => {
-						let msg = "Invalid call, first argument must be a variant of self";
+				// First argument should be some variant of `self`, or `runtime: &mut T::Runtime`.
+				let takes_runtime = match method.sig.inputs.first() {
+					Some(syn::FnArg::Receiver(_)) => false,
+					Some(syn::FnArg::Typed(arg)) => {
+						check_runtime_arg(arg)?;
+						true
+					},
+					None => {
+						let msg = "Invalid call, first argument must be a variant of self or \
+							`runtime: &mut T::Runtime`";
 						return Err(syn::Error::new(method.sig.span(), msg))
 					},
-				}
+				};
 
 				// The second argument should be the `caller: T::AccountId` argument.
 				match method.sig.inputs.iter().skip(1).next() {
@@ -74,7 +85,7 @@ impl CallDef {
 
 				let fn_name = method.sig.ident.clone();
 
-				// Parsing the rest of the args. Skipping 2 for `self` and `caller`.
+				// Parsing the rest of the args. Skipping 2 for `self` (or `runtime`) and `caller`.
 				for arg in method.sig.inputs.iter().skip(2) {
 					// All arguments should be typed.
 					let arg = if let syn::FnArg::Typed(arg) = arg {
@@ -95,8 +106,14 @@ impl CallDef {
 					args.push((arg_ident, arg.ty.clone()));
 				}
 
+				// Parse the optional `#[weight(...)]` attribute.
+				let mut weight = None;
+				for attr in method.attrs.iter().filter(|attr| attr.path().is_ident("weight")) {
+					weight = Some(attr.parse_args::<syn::Expr>()?);
+				}
+
 				// Store all the function name and the arg data for the function.
-				methods.push(CallVariantDef { name: fn_name, args });
+				methods.push(CallVariantDef { name: fn_name, takes_runtime, args, weight });
 			}
 		}
 
@@ -139,3 +156,48 @@ pub fn check_caller_arg(arg: &syn::PatType) -> syn::Result<()> {
 
 	Ok(())
 }
+
+/// Check runtime arg is exactly: `runtime: &mut T::Runtime`.
+pub fn check_runtime_arg(arg: &syn::PatType) -> syn::Result<()> {
+	pub struct CheckRuntimeArg;
+	impl syn::parse::Parse for CheckRuntimeArg {
+		fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+			input.parse::<syn::Token![&]>()?;
+			input.parse::<syn::Token![mut]>()?;
+			input.parse::<keyword::T>()?;
+			input.parse::<syn::Token![::]>()?;
+			input.parse::<keyword::Runtime>()?;
+			Ok(Self)
+		}
+	}
+
+	// This checks the arg name is `runtime`.
+	if let syn::Pat::Ident(ident) = &*arg.pat {
+		if &ident.ident != "runtime" {
+			let msg = "Invalid name for first parameter: expected `runtime: &mut T::Runtime`";
+			return Err(syn::Error::new(ident.span(), msg))
+		}
+	}
+
+	let ty = &arg.ty;
+	syn::parse2::<CheckRuntimeArg>(ty.to_token_stream()).map_err(|e| {
+		let msg = "Invalid type for first parameter: expected `runtime: &mut T::Runtime`";
+		let mut err = syn::Error::new(ty.span(), msg);
+		err.combine(e);
+		err
+	})?;
+
+	Ok(())
+}
+
+/// Remove the `#[weight(...)]` attributes from the callable functions, since they are only used
+/// by this macro and are not valid Rust attributes.
+pub fn strip_weight_attrs(item: &mut syn::Item) {
+	if let syn::Item::Impl(item_impl) = item {
+		for item in item_impl.items.iter_mut() {
+			if let syn::ImplItem::Fn(method) = item {
+				method.attrs.retain(|attr| !attr.path().is_ident("weight"));
+			}
+		}
+	}
+}
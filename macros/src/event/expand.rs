@@ -0,0 +1,99 @@
+use super::parse::EventDef;
+use quote::quote;
+
+/// See the `fn event` docs at the `lib.rs` of this crate for a high level definition.
+pub fn expand_event(def: EventDef) -> proc_macro2::TokenStream {
+	let EventDef { event_enum, variants } = def;
+
+	// This is a vector of all the event names.
+	let variant_name = variants.iter().map(|variant| &variant.name).collect::<Vec<_>>();
+	let variant_str = variant_name.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+
+	// This is a nested vector of all the field names for each of the events in `variant_name`.
+	let fields_name = variants
+		.iter()
+		.map(|variant| variant.fields.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>())
+		.collect::<Vec<_>>();
+	let fields_str = fields_name
+		.iter()
+		.map(|names| names.iter().map(|name| name.to_string()).collect::<Vec<_>>())
+		.collect::<Vec<_>>();
+
+	// All the field types across all events, used to bound the generated trait impls.
+	let all_fields_type =
+		variants.iter().flat_map(|variant| variant.fields.iter().map(|(_, type_)| type_));
+	let all_fields_type = all_fields_type.collect::<Vec<_>>();
+
+	// This is a nested vector of the fields which hold accounts for each event.
+	let account_fields = variants.iter().map(|variant| variant.account_fields()).collect::<Vec<_>>();
+
+	// This quote block implements `Clone`, `Debug` and the JSON encoding for the `Event` enum, and
+	// a way to find all the accounts an event is about. An event is encoded as
+	// `{"EventName": {"field": value}}`.
+	let event_impl = quote! {
+		impl<T: Config> Clone for #event_enum<T>
+		where
+			#( #all_fields_type: Clone ),*
+		{
+			fn clone(&self) -> Self {
+				match self {
+					#(
+						#event_enum::#variant_name { #( #fields_name ),* } => #event_enum::#variant_name {
+							#( #fields_name: #fields_name.clone() ),*
+						},
+					)*
+				}
+			}
+		}
+
+		impl<T: Config> core::fmt::Debug for #event_enum<T>
+		where
+			#( #all_fields_type: core::fmt::Debug ),*
+		{
+			fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+				match self {
+					#(
+						#event_enum::#variant_name { #( #fields_name ),* } => f
+							.debug_struct(#variant_str)
+							#( .field(#fields_str, #fields_name) )*
+							.finish(),
+					)*
+				}
+			}
+		}
+
+		impl<T: Config> crate::json::ToJson for #event_enum<T>
+		where
+			#( #all_fields_type: crate::json::ToJson ),*
+		{
+			fn to_json(&self) -> crate::json::Value {
+				match self {
+					#(
+						#event_enum::#variant_name { #( #fields_name ),* } => crate::json::Value::object([(
+							#variant_str,
+							crate::json::Value::object([
+								#( (#fields_str, crate::json::ToJson::to_json(#fields_name)) ),*
+							]),
+						)]),
+					)*
+				}
+			}
+		}
+
+		impl<T: Config> #event_enum<T> {
+			/// All the accounts this event is about.
+			pub fn accounts(&self) -> Vec<&T::AccountId> {
+				match self {
+					#(
+						#event_enum::#variant_name { #( #account_fields, )* .. } => {
+							vec![ #( #account_fields ),* ]
+						},
+					)*
+				}
+			}
+		}
+	};
+
+	// Return the generated code.
+	event_impl.into()
+}
@@ -0,0 +1,72 @@
+use quote::ToTokens;
+use syn::spanned::Spanned;
+
+/// This object will collect all the information we need to keep while parsing the `Event` enum.
+#[derive(Debug)]
+pub struct EventDef {
+	/// This is the name of the enum used by the pallet. We mostly assume it is `Event`.
+	pub event_enum: syn::Ident,
+	/// This is the list of events the pallet can emit. See `EventVariantDef`.
+	pub variants: Vec<EventVariantDef>,
+}
+
+/// This is the metadata we keep about each event.
+#[derive(Debug)]
+pub struct EventVariantDef {
+	/// The variant name.
+	pub name: syn::Ident,
+	/// Information on the fields of the variant: `(name, type)`.
+	pub fields: Vec<(syn::Ident, syn::Type)>,
+}
+
+impl EventVariantDef {
+	/// The names of the fields which hold an account, i.e. have the type `T::AccountId`.
+	pub fn account_fields(&self) -> Vec<syn::Ident> {
+		self.fields
+			.iter()
+			.filter(|(_, type_)| is_account_id(type_))
+			.map(|(name, _)| name.clone())
+			.collect()
+	}
+}
+
+impl EventDef {
+	pub fn try_from(item: syn::Item) -> syn::Result<Self> {
+		// First we check that we are parsing an `enum`.
+		let item_enum = if let syn::Item::Enum(item) = item {
+			item
+		} else {
+			return Err(syn::Error::new(item.span(), "Invalid pallet::event, expected item enum"))
+		};
+
+		let event_enum = item_enum.ident;
+
+		// Here is where we will store all the events.
+		let mut variants = vec![];
+		for variant in item_enum.variants {
+			// We only support events with named fields, so they can be encoded with their names.
+			let fields = match variant.fields {
+				syn::Fields::Named(fields) => fields.named,
+				syn::Fields::Unit => Default::default(),
+				syn::Fields::Unnamed(_) => {
+					let msg = "Invalid pallet::event, fields must be named";
+					return Err(syn::Error::new(variant.span(), msg))
+				},
+			};
+
+			let fields = fields
+				.into_iter()
+				.map(|field| (field.ident.expect("Named fields have an ident; qed"), field.ty))
+				.collect();
+
+			variants.push(EventVariantDef { name: variant.ident, fields });
+		}
+
+		Ok(Self { event_enum, variants })
+	}
+}
+
+/// Check if a type is exactly `T::AccountId`.
+fn is_account_id(type_: &syn::Type) -> bool {
+	type_.to_token_stream().to_string() == "T :: AccountId"
+}
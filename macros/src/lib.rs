@@ -1,6 +1,22 @@
 mod call;
+mod event;
 mod runtime;
 
+/// Expand the callable functions of a pallet.
+///
+/// Every function in the `impl` block must take `&mut self` and `caller: T::AccountId` as its
+/// first two parameters. A function which needs other pallets, for example the `T::Currency` of
+/// the pallet, can take `runtime: &mut T::Runtime` instead of `&mut self`. The pallet's `Config`
+/// must then declare `type Runtime: AsMut<Pallet<Self>>`, along with `AsMut` bounds for the other
+/// pallets it needs. This generates:
+/// - `enum Call` - with a variant for each function, holding the rest of its arguments.
+/// - `fn dispatch()` - which routes each `Call` to its function, given the runtime and the
+///   `caller`. The pallet is borrowed from the runtime with `AsMut`.
+/// - `Clone` and a JSON encoding for `Call`, where a call is encoded as `{"fn_name": {"arg": value}}`.
+/// - `fn weight()` - the weight of a call. A function can declare it with `#[weight(expr)]`,
+///   where `expr` can use the arguments of the call by reference, otherwise
+///   `support::DEFAULT_WEIGHT` is used.
+/// - `fn metadata()` - which describes every call and its arguments.
 #[proc_macro_attribute]
 pub fn call(
 	attr: proc_macro::TokenStream,
@@ -9,6 +25,21 @@ pub fn call(
 	call::call(attr, item)
 }
 
+/// Expand the `Event` enum of a pallet.
+///
+/// Every variant must use named fields. This generates:
+/// - `Clone` and `Debug` implementations, bounded only on the types of the fields.
+/// - a JSON encoding, where each event is encoded as `{"EventName": {"field": value}}`.
+/// - `fn accounts()` - which returns every field of the event with the type `T::AccountId`. This
+///   is used to find the events which concern an account.
+#[proc_macro_attribute]
+pub fn event(
+	attr: proc_macro::TokenStream,
+	item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+	event::event(attr, item)
+}
+
 /// Expand the `Runtime` definition.
 ///
 /// This generates function implementations on `Runtime`:
@@ -16,15 +47,28 @@ pub fn call(
 ///   included in the runtime.
 /// - `fn execute_block()` - which handles basic logic for executing a block of extrinsics. It does
 ///   basic actions like incrementing the block number and checking the block to be executed has a
-///   valid block number.
+///   valid block number. It charges the fee of each extrinsic through `support::ChargeFee`, which
+///   the runtime must implement, and returns a `types::BlockReceipt` with the outcome of each
+///   extrinsic. At the end of the block, it calls `support::Hooks::on_finalize` for each pallet
+///   with the whole runtime, so every pallet must implement `support::Hooks<Runtime>`.
 ///
 /// This also generates code needed for dispatching calls to the pallets:
 /// - Note: For simplicity, we assume that the system pallet is not callable.
 /// - `enum RuntimeCall` - an "outer"-enum representing the accumulation of all possible calls to
-///   all pallets. The system pallet is not included.
+///   all pallets. The system pallet is not included. It can be encoded to and decoded from JSON,
+///   using the encoding generated for each pallet's `Call` by `#[macros::call]`, and its
+///   `metadata()` lists every pallet along with its calls and their arguments.
+/// - `enum RuntimeEvent` - an "outer"-enum representing the accumulation of all the events of all
+///   pallets. Each pallet must define an `Event` enum with `#[macros::event]`, and a
+///   `take_events()` function which drains the events it deposited. After each extrinsic, these
+///   events are moved into the system pallet.
 /// - implements the trait `support::Dispatch` to dispatch calls to the appropriate pallet. Basic
 ///   logic like incrementing the nonce of the user is included in the generated code. The system
 ///   pallet is not included.
+/// - implements `support::StorageSize` as the sum of the storage sizes of its pallets, including
+///   system, so every pallet must implement it.
+/// - implements `AsMut` for each pallet, including system, so pallets can access one another
+///   through their `T::Runtime`.
 #[proc_macro_attribute]
 pub fn runtime(
 	attr: proc_macro::TokenStream,
@@ -9,6 +9,8 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 	let pallet_names = pallets.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
 	// This is a vector of all the pallet types, not including system.
 	let pallet_types = pallets.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>();
+	// The pallet names as strings, used to identify the pallet in the JSON encoding of calls.
+	let pallet_strs = pallet_names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
 
 	// This quote block implements functions on the `Runtime` struct.
 	let runtime_impl = quote! {
@@ -25,21 +27,55 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 			}
 
 			// Execute a block of extrinsics. Increments the block number.
-			fn execute_block(&mut self, block: types::Block) -> crate::support::DispatchResult {
+			// Events from the previous block are cleared, and the events of each extrinsic are
+			// collected in the system pallet.
+			//
+			// The fee of each extrinsic is charged from its caller with `support::ChargeFee` before
+			// it is dispatched. A failed extrinsic does not fail the block, its outcome is recorded
+			// in the returned receipt instead.
+			//
+			// Once every extrinsic is executed, `support::Hooks::on_finalize` is called for each
+			// pallet. The events it deposits are recorded with the index following the last
+			// extrinsic.
+			fn execute_block(&mut self, block: types::Block) -> Result<types::BlockReceipt, crate::support::DispatchError> {
 				self.system.inc_block_number();
 				if block.header.block_number != self.system.block_number() {
 					return Err(&"block number does not match what is expected")
 				}
+				self.system.reset_events();
+				let mut receipt = crate::support::BlockReceipt {
+					block_number: block.header.block_number,
+					extrinsics: Vec::new(),
+				};
 				for (i, support::Extrinsic { caller, call }) in block.extrinsics.into_iter().enumerate() {
 					self.system.inc_nonce(&caller);
-					let _res = self.dispatch(caller, call).map_err(|e| {
-						eprintln!(
-							"Extrinsic Error\n\tBlock Number: {}\n\tExtrinsic Number: {}\n\tError: {}",
-							block.header.block_number, i, e
-						)
-					});
+					let weight = crate::support::GetWeight::weight(&call);
+					let (result, fee) = match crate::support::ChargeFee::charge_fee(self, &caller, weight) {
+						Ok(fee) => (self.dispatch(caller, call), fee),
+						Err(e) => (Err(e), Default::default()),
+					};
+					let events = self.collect_events(i as u32);
+					receipt.extrinsics.push(crate::support::ExtrinsicReceipt { result, weight, fee, events });
 				}
-				Ok(())
+				#(
+					<#pallet_types as crate::support::Hooks<Self>>::on_finalize(self);
+				)*
+				self.collect_events(receipt.extrinsics.len() as u32);
+				Ok(receipt)
+			}
+
+			// Move the events deposited by each pallet into the system pallet, and return them.
+			fn collect_events(&mut self, extrinsic_index: u32) -> Vec<RuntimeEvent> {
+				let mut events = Vec::new();
+				#(
+					for event in self.#pallet_names.take_events() {
+						events.push(RuntimeEvent::#pallet_names(event));
+					}
+				)*
+				for event in &events {
+					self.system.deposit_event(extrinsic_index, event.clone());
+				}
+				events
 			}
 		}
 	};
@@ -51,10 +87,99 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 		//
 		// The parsed function names will be `snake_case`, and that will show up in the enum.
 		#[allow(non_camel_case_types)]
+		#[derive(Clone)]
 		pub enum RuntimeCall {
 			#( #pallet_names(#pallet_names::Call<#runtime_struct>) ),*
 		}
 
+		// The weight of a call, as declared by its pallet.
+		impl crate::support::GetWeight for RuntimeCall {
+			fn weight(&self) -> crate::support::Weight {
+				match self {
+					#( RuntimeCall::#pallet_names(call) => call.weight() ),*
+				}
+			}
+		}
+
+		impl RuntimeCall {
+			// Describes all the calls of all the pallets, so they can be built without knowing the
+			// pallets in advance.
+			pub fn metadata() -> Vec<crate::support::PalletMetadata> {
+				vec![
+					#(
+						crate::support::PalletMetadata {
+							name: #pallet_strs,
+							calls: #pallet_names::Call::<#runtime_struct>::metadata(),
+						}
+					),*
+				]
+			}
+		}
+
+		// A runtime call is encoded as `{"pallet_name": <pallet call>}`.
+		impl crate::json::ToJson for RuntimeCall {
+			fn to_json(&self) -> crate::json::Value {
+				match self {
+					#(
+						RuntimeCall::#pallet_names(call) => crate::json::Value::object([
+							(#pallet_strs, crate::json::ToJson::to_json(call)),
+						]),
+					)*
+				}
+			}
+		}
+
+		impl crate::json::FromJson for RuntimeCall {
+			fn from_json(value: &crate::json::Value) -> Result<Self, &'static str> {
+				let (name, call) = value.as_variant()?;
+				match name {
+					#(
+						#pallet_strs => Ok(RuntimeCall::#pallet_names(
+							crate::json::FromJson::from_json(call)?,
+						)),
+					)*
+					_ => Err("Unknown pallet"),
+				}
+			}
+		}
+
+		// These are all the events which can be emitted by the runtime, in the same way as
+		// `RuntimeCall` accumulates all the calls.
+		#[allow(non_camel_case_types)]
+		#[derive(Clone, Debug)]
+		pub enum RuntimeEvent {
+			#( #pallet_names(#pallet_names::Event<#runtime_struct>) ),*
+		}
+
+		impl RuntimeEvent {
+			// The name of the pallet which emitted this event.
+			pub fn pallet(&self) -> &'static str {
+				match self {
+					#( RuntimeEvent::#pallet_names(_) => #pallet_strs ),*
+				}
+			}
+
+			// All the accounts this event is about.
+			pub fn accounts(&self) -> Vec<&<#runtime_struct as system::Config>::AccountId> {
+				match self {
+					#( RuntimeEvent::#pallet_names(event) => event.accounts() ),*
+				}
+			}
+		}
+
+		// A runtime event is encoded as `{"pallet_name": <pallet event>}`.
+		impl crate::json::ToJson for RuntimeEvent {
+			fn to_json(&self) -> crate::json::Value {
+				match self {
+					#(
+						RuntimeEvent::#pallet_names(event) => crate::json::Value::object([
+							(#pallet_strs, crate::json::ToJson::to_json(event)),
+						]),
+					)*
+				}
+			}
+		}
+
 		impl crate::support::Dispatch for #runtime_struct {
 			type Caller = <Runtime as system::Config>::AccountId;
 			type Call = RuntimeCall;
@@ -73,13 +198,37 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 				match runtime_call {
 					#(
 						RuntimeCall::#pallet_names(call) => {
-							self.#pallet_names.dispatch(caller, call)?;
+							call.dispatch(self, caller)?;
 						}
 					),*
 				}
 				Ok(())
 			}
 		}
+
+		// The state of the runtime is the state of all of its pallets.
+		impl crate::support::StorageSize for #runtime_struct {
+			fn storage_size(&self) -> usize {
+				crate::support::StorageSize::storage_size(&self.system)
+					#( + crate::support::StorageSize::storage_size(&self.#pallet_names) )*
+			}
+		}
+
+		// Give access to each pallet from the runtime, so calls and other pallets can reach a pallet
+		// through `T::Runtime: AsMut<Pallet<T>>` without knowing the runtime.
+		impl AsMut<system::Pallet<#runtime_struct>> for #runtime_struct {
+			fn as_mut(&mut self) -> &mut system::Pallet<#runtime_struct> {
+				&mut self.system
+			}
+		}
+
+		#(
+			impl AsMut<#pallet_types> for #runtime_struct {
+				fn as_mut(&mut self) -> &mut #pallet_types {
+					&mut self.#pallet_names
+				}
+			}
+		)*
 	};
 
 	// We combine and return all the generated code.
@@ -0,0 +1,453 @@
+use num::{CheckedAdd, CheckedSub, Zero};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use crate::json::{ToJson, Value};
+use crate::support::{DispatchResult, Hooks, StorageSize};
+
+/// The longest name or symbol an asset can have, in bytes.
+const METADATA_LIMIT: usize = 32;
+
+pub trait Config: crate::system::Config {
+    type AssetId: Debug + Ord + Copy;
+    type Balance: Debug + Zero + CheckedAdd + CheckedSub + Ord + Copy;
+}
+
+/// An asset created with `create`. The `owner` manages its metadata, and the `admin` can mint,
+/// burn and freeze it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetDetails<AccountId, Balance> {
+    pub owner: AccountId,
+    pub admin: AccountId,
+    pub supply: Balance,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// The holding of an asset by an account. A frozen account can not send the asset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetAccount<Balance> {
+    pub balance: Balance,
+    pub is_frozen: bool,
+}
+
+/// Holdings are keyed by the asset and the account holding it.
+type HoldingKey<T> = (
+    <T as Config>::AssetId,
+    <T as crate::system::Config>::AccountId,
+);
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+    assets: BTreeMap<T::AssetId, AssetDetails<T::AccountId, T::Balance>>,
+    metadata: BTreeMap<T::AssetId, AssetMetadata>,
+    accounts: BTreeMap<HoldingKey<T>, AssetAccount<T::Balance>>,
+    events: Vec<Event<T>>,
+}
+
+#[macros::event]
+pub enum Event<T: Config> {
+    Created {
+        asset_id: T::AssetId,
+        owner: T::AccountId,
+        admin: T::AccountId,
+    },
+    Issued {
+        asset_id: T::AssetId,
+        owner: T::AccountId,
+        amount: T::Balance,
+    },
+    Burned {
+        asset_id: T::AssetId,
+        owner: T::AccountId,
+        amount: T::Balance,
+    },
+    Transferred {
+        asset_id: T::AssetId,
+        from: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+    },
+    Frozen {
+        asset_id: T::AssetId,
+        who: T::AccountId,
+    },
+    Thawed {
+        asset_id: T::AssetId,
+        who: T::AccountId,
+    },
+    MetadataSet {
+        asset_id: T::AssetId,
+        name: String,
+        symbol: String,
+        decimals: u8,
+    },
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Create a new asset owned by the caller, with no supply.
+    pub fn create(
+        &mut self,
+        caller: T::AccountId,
+        asset_id: T::AssetId,
+        admin: T::AccountId,
+    ) -> DispatchResult {
+        if self.assets.contains_key(&asset_id) {
+            return Err("Asset already exists");
+        }
+
+        self.assets.insert(
+            asset_id,
+            AssetDetails {
+                owner: caller.clone(),
+                admin: admin.clone(),
+                supply: T::Balance::zero(),
+            },
+        );
+        self.deposit_event(Event::Created {
+            asset_id,
+            owner: caller,
+            admin,
+        });
+        Ok(())
+    }
+
+    pub fn mint(
+        &mut self,
+        caller: T::AccountId,
+        asset_id: T::AssetId,
+        beneficiary: T::AccountId,
+        amount: T::Balance,
+    ) -> DispatchResult {
+        let details = self.ensure_admin(asset_id, &caller)?;
+        let supply = details
+            .supply
+            .checked_add(&amount)
+            .ok_or("Overflow when increasing supply")?;
+        let balance = self
+            .balance(asset_id, &beneficiary)
+            .checked_add(&amount)
+            .ok_or("Overflow when adding to balance")?;
+
+        self.set_supply(asset_id, supply);
+        self.set_balance(asset_id, &beneficiary, balance);
+        self.deposit_event(Event::Issued {
+            asset_id,
+            owner: beneficiary,
+            amount,
+        });
+        Ok(())
+    }
+
+    pub fn burn(
+        &mut self,
+        caller: T::AccountId,
+        asset_id: T::AssetId,
+        who: T::AccountId,
+        amount: T::Balance,
+    ) -> DispatchResult {
+        let details = self.ensure_admin(asset_id, &caller)?;
+        let balance = self
+            .balance(asset_id, &who)
+            .checked_sub(&amount)
+            .ok_or("Insufficient balance")?;
+        // The balance of an account is part of the supply, so this can not underflow.
+        let supply = details.supply - amount;
+
+        self.set_supply(asset_id, supply);
+        self.set_balance(asset_id, &who, balance);
+        self.deposit_event(Event::Burned {
+            asset_id,
+            owner: who,
+            amount,
+        });
+        Ok(())
+    }
+
+    pub fn transfer(
+        &mut self,
+        caller: T::AccountId,
+        asset_id: T::AssetId,
+        to: T::AccountId,
+        amount: T::Balance,
+    ) -> DispatchResult {
+        self.asset(asset_id).ok_or("Asset does not exist")?;
+        if self.is_frozen(asset_id, &caller) {
+            return Err("Account is frozen");
+        }
+
+        let caller_balance = self
+            .balance(asset_id, &caller)
+            .checked_sub(&amount)
+            .ok_or("Insufficient balance")?;
+        let to_balance = self
+            .balance(asset_id, &to)
+            .checked_add(&amount)
+            .ok_or("Overflow when adding to balance")?;
+
+        // A transfer to oneself only has to pass the checks.
+        if caller != to {
+            self.set_balance(asset_id, &caller, caller_balance);
+            self.set_balance(asset_id, &to, to_balance);
+        }
+        self.deposit_event(Event::Transferred {
+            asset_id,
+            from: caller,
+            to,
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Prevent `who` from sending the asset.
+    pub fn freeze(
+        &mut self,
+        caller: T::AccountId,
+        asset_id: T::AssetId,
+        who: T::AccountId,
+    ) -> DispatchResult {
+        self.ensure_admin(asset_id, &caller)?;
+        self.set_frozen(asset_id, &who, true);
+        self.deposit_event(Event::Frozen { asset_id, who });
+        Ok(())
+    }
+
+    pub fn thaw(
+        &mut self,
+        caller: T::AccountId,
+        asset_id: T::AssetId,
+        who: T::AccountId,
+    ) -> DispatchResult {
+        self.ensure_admin(asset_id, &caller)?;
+        self.set_frozen(asset_id, &who, false);
+        self.deposit_event(Event::Thawed { asset_id, who });
+        Ok(())
+    }
+
+    pub fn set_metadata(
+        &mut self,
+        caller: T::AccountId,
+        asset_id: T::AssetId,
+        name: String,
+        symbol: String,
+        decimals: u8,
+    ) -> DispatchResult {
+        let details = self.asset(asset_id).ok_or("Asset does not exist")?;
+        if details.owner != caller {
+            return Err("Caller is not the owner of the asset");
+        }
+        if name.len() > METADATA_LIMIT || symbol.len() > METADATA_LIMIT {
+            return Err("Metadata is too long");
+        }
+
+        self.metadata.insert(
+            asset_id,
+            AssetMetadata {
+                name: name.clone(),
+                symbol: symbol.clone(),
+                decimals,
+            },
+        );
+        self.deposit_event(Event::MetadataSet {
+            asset_id,
+            name,
+            symbol,
+            decimals,
+        });
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Self {
+            assets: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+            accounts: BTreeMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn asset(&self, asset_id: T::AssetId) -> Option<&AssetDetails<T::AccountId, T::Balance>> {
+        self.assets.get(&asset_id)
+    }
+
+    pub fn metadata(&self, asset_id: T::AssetId) -> Option<&AssetMetadata> {
+        self.metadata.get(&asset_id)
+    }
+
+    pub fn balance(&self, asset_id: T::AssetId, who: &T::AccountId) -> T::Balance {
+        self.accounts
+            .get(&(asset_id, who.clone()))
+            .map_or(T::Balance::zero(), |account| account.balance)
+    }
+
+    pub fn is_frozen(&self, asset_id: T::AssetId, who: &T::AccountId) -> bool {
+        self.accounts
+            .get(&(asset_id, who.clone()))
+            .is_some_and(|account| account.is_frozen)
+    }
+
+    /// Check that the asset exists and that `who` is its admin.
+    fn ensure_admin(
+        &self,
+        asset_id: T::AssetId,
+        who: &T::AccountId,
+    ) -> Result<AssetDetails<T::AccountId, T::Balance>, &'static str> {
+        let details = self.asset(asset_id).ok_or("Asset does not exist")?;
+        if &details.admin != who {
+            return Err("Caller is not the admin of the asset");
+        }
+        Ok(details.clone())
+    }
+
+    fn set_supply(&mut self, asset_id: T::AssetId, supply: T::Balance) {
+        if let Some(details) = self.assets.get_mut(&asset_id) {
+            details.supply = supply;
+        }
+    }
+
+    fn set_balance(&mut self, asset_id: T::AssetId, who: &T::AccountId, balance: T::Balance) {
+        let is_frozen = self.is_frozen(asset_id, who);
+        self.write_account(asset_id, who, AssetAccount { balance, is_frozen });
+    }
+
+    fn set_frozen(&mut self, asset_id: T::AssetId, who: &T::AccountId, is_frozen: bool) {
+        let balance = self.balance(asset_id, who);
+        self.write_account(asset_id, who, AssetAccount { balance, is_frozen });
+    }
+
+    /// Store the holding of an account, removing it once it is empty and not frozen.
+    fn write_account(
+        &mut self,
+        asset_id: T::AssetId,
+        who: &T::AccountId,
+        account: AssetAccount<T::Balance>,
+    ) {
+        let key = (asset_id, who.clone());
+        if account.balance.is_zero() && !account.is_frozen {
+            self.accounts.remove(&key);
+        } else {
+            self.accounts.insert(key, account);
+        }
+    }
+
+    fn deposit_event(&mut self, event: Event<T>) {
+        self.events.push(event);
+    }
+
+    pub fn take_events(&mut self) -> Vec<Event<T>> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl<T: Config> StorageSize for Pallet<T> {
+    fn storage_size(&self) -> usize {
+        self.assets.len() + self.metadata.len() + self.accounts.len()
+    }
+}
+
+impl<T: Config, Runtime> Hooks<Runtime> for Pallet<T> {}
+
+impl<AccountId: ToJson, Balance: ToJson> ToJson for AssetDetails<AccountId, Balance> {
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("owner", self.owner.to_json()),
+            ("admin", self.admin.to_json()),
+            ("supply", self.supply.to_json()),
+        ])
+    }
+}
+
+impl ToJson for AssetMetadata {
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("name", self.name.to_json()),
+            ("symbol", self.symbol.to_json()),
+            ("decimals", self.decimals.to_json()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    struct TestConfig;
+    impl crate::system::Config for TestConfig {
+        type AccountId = String;
+        type BlockNumber = u32;
+        type Nonce = u32;
+        type RuntimeEvent = ();
+    }
+    impl super::Config for TestConfig {
+        type AssetId = u32;
+        type Balance = u128;
+    }
+
+    #[test]
+    fn create_and_mint() {
+        let mut assets = super::Pallet::<TestConfig>::new();
+        let alice = "Alice".to_string();
+        let bob = "Bob".to_string();
+
+        assets.create(alice.clone(), 1, bob.clone()).unwrap();
+        let res = assets.create(bob.clone(), 1, bob.clone());
+        assert_eq!(res, Err("Asset already exists"));
+
+        // Only the admin can mint and burn.
+        let res = assets.mint(alice.clone(), 1, alice.clone(), 100);
+        assert_eq!(res, Err("Caller is not the admin of the asset"));
+        assets.mint(bob.clone(), 1, alice.clone(), 100).unwrap();
+        assets.burn(bob.clone(), 1, alice.clone(), 30).unwrap();
+        let res = assets.burn(bob.clone(), 1, alice.clone(), 80);
+        assert_eq!(res, Err("Insufficient balance"));
+        assert_eq!(assets.balance(1, &alice), 70);
+        assert_eq!(assets.asset(1).unwrap().supply, 70);
+
+        // Only the owner can set the metadata.
+        let res = assets.set_metadata(bob.clone(), 1, "Loyalty".into(), "LOY".into(), 2);
+        assert_eq!(res, Err("Caller is not the owner of the asset"));
+        assets
+            .set_metadata(alice.clone(), 1, "Loyalty".into(), "LOY".into(), 2)
+            .unwrap();
+        assert_eq!(assets.metadata(1).unwrap().symbol, "LOY");
+
+        let res = assets.mint(bob.clone(), 2, alice.clone(), 100);
+        assert_eq!(res, Err("Asset does not exist"));
+    }
+
+    #[test]
+    fn transfer_and_freeze() {
+        let mut assets = super::Pallet::<TestConfig>::new();
+        let alice = "Alice".to_string();
+        let bob = "Bob".to_string();
+
+        assets.create(alice.clone(), 1, alice.clone()).unwrap();
+        assets.mint(alice.clone(), 1, alice.clone(), 100).unwrap();
+
+        assets.transfer(alice.clone(), 1, bob.clone(), 40).unwrap();
+        assets
+            .transfer(alice.clone(), 1, alice.clone(), 60)
+            .unwrap();
+        assert_eq!(assets.balance(1, &alice), 60);
+        assert_eq!(assets.balance(1, &bob), 40);
+        let res = assets.transfer(bob.clone(), 1, alice.clone(), 50);
+        assert_eq!(res, Err("Insufficient balance"));
+
+        // A frozen account can still receive the asset, but not send it.
+        assets.freeze(alice.clone(), 1, bob.clone()).unwrap();
+        assets.transfer(alice.clone(), 1, bob.clone(), 10).unwrap();
+        let res = assets.transfer(bob.clone(), 1, alice.clone(), 10);
+        assert_eq!(res, Err("Account is frozen"));
+
+        assets.thaw(alice.clone(), 1, bob.clone()).unwrap();
+        assets.transfer(bob.clone(), 1, alice.clone(), 50).unwrap();
+        assert_eq!(assets.balance(1, &alice), 100);
+        assert_eq!(assets.balance(1, &bob), 0);
+    }
+}
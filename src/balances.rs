@@ -1,68 +1,692 @@
 use num::{CheckedAdd, CheckedSub, Zero};
 use std::collections::BTreeMap;
 
+use crate::support::{
+    Currency, DispatchError, DispatchResult, ExistenceRequirement, Hooks, LockIdentifier,
+    LockableCurrency, ReservableCurrency, StorageSize, WithdrawReasons,
+};
+use crate::system;
+
 pub trait Config: crate::system::Config {
-    type Balance: Zero + CheckedAdd + CheckedSub + Copy;
+    type Balance: Zero + CheckedAdd + CheckedSub + Ord + Copy;
+    /// The smallest total balance an account can hold. An account left with less is removed, and
+    /// its remaining balance is burned.
+    const EXISTENTIAL_DEPOSIT: Self::Balance;
+}
+
+/// The balances of an account. Only the `free` balance can be spent, the `reserved` balance is
+/// held by other pallets, for example as a deposit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AccountData<Balance> {
+    pub free: Balance,
+    pub reserved: Balance,
+}
+
+/// Prevents the free balance of an account from going below `amount` for the given reasons.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceLock<Balance> {
+    pub id: LockIdentifier,
+    pub amount: Balance,
+    pub reasons: WithdrawReasons,
 }
 
-#[derive(Debug)]
+/// Allowances are keyed by the owner of the funds and the delegate allowed to spend them.
+type ApprovalKey<T> = (
+    <T as crate::system::Config>::AccountId,
+    <T as crate::system::Config>::AccountId,
+);
+
+#[derive(Debug, Clone)]
 pub struct Pallet<T: Config> {
-    balances: BTreeMap<T::AccountId, T::Balance>,
+    accounts: BTreeMap<T::AccountId, AccountData<T::Balance>>,
+    /// The sum of the free and reserved balances of every account.
+    total_issuance: T::Balance,
+    locks: BTreeMap<T::AccountId, Vec<BalanceLock<T::Balance>>>,
+    /// How much each delegate can still transfer from the free balance of an owner.
+    approvals: BTreeMap<ApprovalKey<T>, T::Balance>,
+    /// The accounts removed during the current block, whose nonce is reset when it is finalized.
+    reaped: Vec<T::AccountId>,
+    events: Vec<Event<T>>,
+}
+
+#[macros::event]
+pub enum Event<T: Config> {
+    Transfer {
+        from: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+    },
+    Reserved {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    Unreserved {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    Slashed {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    ReserveRepatriated {
+        from: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+    },
+    Minted {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    Burned {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    ApprovedTransfer {
+        owner: T::AccountId,
+        delegate: T::AccountId,
+        amount: T::Balance,
+    },
+    ApprovalCancelled {
+        owner: T::AccountId,
+        delegate: T::AccountId,
+    },
+    TransferredApproved {
+        owner: T::AccountId,
+        delegate: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+    },
+    DustLost {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
 }
 
 #[macros::call]
 impl<T: Config> Pallet<T> {
+    /// Transfer funds, removing the account of the caller if it is left below the existential
+    /// deposit.
+    #[weight(2)]
     pub fn transfer(
         &mut self,
         caller: T::AccountId,
         to: T::AccountId,
         amount: T::Balance,
-    ) -> Result<(), &'static str> {
-        let caller_balance = self.balance(&caller);
-        let to_balance = self.balance(&to);
+    ) -> DispatchResult {
+        Currency::transfer(self, &caller, &to, amount, ExistenceRequirement::AllowDeath)
+    }
 
-        let new_caller_balance = caller_balance
-            .checked_sub(&amount)
-            .ok_or("Insufficient balance")?;
+    /// Transfer funds, failing if the caller would be left below the existential deposit.
+    #[weight(2)]
+    pub fn transfer_keep_alive(
+        &mut self,
+        caller: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+    ) -> DispatchResult {
+        Currency::transfer(self, &caller, &to, amount, ExistenceRequirement::KeepAlive)
+    }
 
-        let new_to_balance = to_balance
-            .checked_add(&amount)
-            .ok_or("Overflow when adding to balance")?;
+    /// Allow `delegate` to transfer up to `amount` from the caller, replacing any previous
+    /// allowance.
+    pub fn approve_transfer(
+        &mut self,
+        caller: T::AccountId,
+        delegate: T::AccountId,
+        amount: T::Balance,
+    ) -> DispatchResult {
+        self.approvals
+            .insert((caller.clone(), delegate.clone()), amount);
+        self.deposit_event(Event::ApprovedTransfer {
+            owner: caller,
+            delegate,
+            amount,
+        });
+        Ok(())
+    }
+
+    pub fn cancel_approval(
+        &mut self,
+        caller: T::AccountId,
+        delegate: T::AccountId,
+    ) -> DispatchResult {
+        self.approvals
+            .remove(&(caller.clone(), delegate.clone()))
+            .ok_or("Approval does not exist")?;
+        self.deposit_event(Event::ApprovalCancelled {
+            owner: caller,
+            delegate,
+        });
+        Ok(())
+    }
 
-        self.set_balance(&caller, new_caller_balance);
-        self.set_balance(&to, new_to_balance);
+    /// Transfer funds from `owner` to `to`, spending the allowance `owner` gave to the caller. A
+    /// delegate can't remove the account of the owner, so the owner always keeps the existential
+    /// deposit.
+    #[weight(2)]
+    pub fn transfer_approved(
+        &mut self,
+        caller: T::AccountId,
+        owner: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+    ) -> DispatchResult {
+        let key = (owner.clone(), caller.clone());
+        let remaining = self
+            .allowance(&owner, &caller)
+            .checked_sub(&amount)
+            .ok_or("Allowance exceeded")?;
+        Currency::transfer(self, &owner, &to, amount, ExistenceRequirement::KeepAlive)?;
 
+        if remaining.is_zero() {
+            self.approvals.remove(&key);
+        } else {
+            self.approvals.insert(key, remaining);
+        }
+        self.deposit_event(Event::TransferredApproved {
+            owner,
+            delegate: caller,
+            to,
+            amount,
+        });
         Ok(())
     }
+
+    /// Destroy funds from the free balance of the caller, reducing the total issuance.
+    pub fn burn(&mut self, caller: T::AccountId, amount: T::Balance) -> DispatchResult {
+        self.burn_from(&caller, amount)
+    }
 }
 
 impl<T: Config> Pallet<T> {
     pub fn new() -> Self {
         Self {
-            balances: BTreeMap::new(),
+            accounts: BTreeMap::new(),
+            total_issuance: T::Balance::zero(),
+            approvals: BTreeMap::new(),
+            locks: BTreeMap::new(),
+            reaped: Vec::new(),
+            events: Vec::new(),
         }
     }
 
+    /// Set the free balance of an account, creating or destroying funds as needed. Outside of
+    /// tests, funds are only created with `mint_into` and destroyed with `burn_from`.
+    #[cfg(test)]
     pub fn set_balance(&mut self, who: &T::AccountId, amount: T::Balance) {
-        self.balances.insert(who.clone(), amount);
+        let mut account = self.account(who);
+        // Tests may set balances which can not be reached otherwise, so the total issuance is
+        // left as is if it would overflow.
+        self.total_issuance = if amount > account.free {
+            self.total_issuance.checked_add(&(amount - account.free))
+        } else {
+            self.total_issuance.checked_sub(&(account.free - amount))
+        }
+        .unwrap_or(self.total_issuance);
+        account.free = amount;
+        self.write_account(who, account);
+    }
+
+    pub fn total_issuance(&self) -> T::Balance {
+        self.total_issuance
+    }
+
+    /// Check that the total issuance is the sum of every balance.
+    pub fn check_issuance(&self) -> DispatchResult {
+        let total = self
+            .accounts
+            .values()
+            .try_fold(T::Balance::zero(), |total, account| {
+                total
+                    .checked_add(&account.free)?
+                    .checked_add(&account.reserved)
+            });
+        if total != Some(self.total_issuance) {
+            return Err("Total issuance does not match the balances");
+        }
+        Ok(())
     }
 
+    /// Create `amount` new funds in the free balance of `who`.
+    pub fn mint_into(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+        let total_issuance = self
+            .total_issuance
+            .checked_add(&amount)
+            .ok_or("Overflow when increasing total issuance")?;
+        let mut account = self.account(who);
+        account.free = account
+            .free
+            .checked_add(&amount)
+            .ok_or("Overflow when adding to balance")?;
+        if !Self::can_exist(&account) {
+            return Err("Existential deposit not met");
+        }
+
+        self.total_issuance = total_issuance;
+        self.write_account(who, account);
+        self.deposit_event(Event::Minted {
+            who: who.clone(),
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Destroy `amount` from the free balance of `who`, which may remove the account.
+    pub fn burn_from(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+        self.withdraw(
+            who,
+            amount,
+            WithdrawReasons::Misc,
+            ExistenceRequirement::AllowDeath,
+        )?;
+        self.deposit_event(Event::Burned {
+            who: who.clone(),
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Like `mint_into`, but never fails. Returns the amount deposited, which is zero if `who`
+    /// could not receive it.
+    pub fn deposit_creating(&mut self, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+        match self.mint_into(who, amount) {
+            Ok(()) => amount,
+            Err(_) => T::Balance::zero(),
+        }
+    }
+
+    /// The free balance of an account, which is what it can spend.
     pub fn balance(&self, who: &T::AccountId) -> T::Balance {
-        *self.balances.get(who).unwrap_or(&T::Balance::zero())
+        self.account(who).free
+    }
+
+    pub fn account(&self, who: &T::AccountId) -> AccountData<T::Balance> {
+        self.accounts.get(who).copied().unwrap_or(AccountData {
+            free: T::Balance::zero(),
+            reserved: T::Balance::zero(),
+        })
+    }
+
+    /// How much `delegate` can still transfer from the free balance of `owner`.
+    pub fn allowance(&self, owner: &T::AccountId, delegate: &T::AccountId) -> T::Balance {
+        self.approvals
+            .get(&(owner.clone(), delegate.clone()))
+            .copied()
+            .unwrap_or(T::Balance::zero())
+    }
+
+    pub fn locks(&self, who: &T::AccountId) -> &[BalanceLock<T::Balance>] {
+        self.locks.get(who).map_or(&[], Vec::as_slice)
+    }
+
+    /// The part of the free balance which is locked for a withdrawal of the given kind. Locks
+    /// overlap, so this is the largest of them rather than their sum.
+    pub fn frozen_balance(&self, who: &T::AccountId, reason: WithdrawReasons) -> T::Balance {
+        self.locks(who)
+            .iter()
+            .filter(|lock| lock.reasons.includes(reason))
+            .map(|lock| lock.amount)
+            .max()
+            .unwrap_or(T::Balance::zero())
+    }
+
+    /// Check that the free balance of `who` can go down to `new_balance` for a withdrawal of the
+    /// given kind.
+    pub fn ensure_can_withdraw(
+        &self,
+        who: &T::AccountId,
+        new_balance: T::Balance,
+        reason: WithdrawReasons,
+    ) -> DispatchResult {
+        if new_balance < self.frozen_balance(who, reason) {
+            return Err("Balance is locked");
+        }
+        Ok(())
+    }
+
+    /// The balances of `who` once `amount` is withdrawn from its free balance, checking its locks
+    /// for `reasons` and whether it can be removed.
+    fn after_withdraw(
+        &self,
+        who: &T::AccountId,
+        amount: T::Balance,
+        reasons: WithdrawReasons,
+        existence: ExistenceRequirement,
+    ) -> Result<AccountData<T::Balance>, DispatchError> {
+        let mut account = self.account(who);
+        account.free = account
+            .free
+            .checked_sub(&amount)
+            .ok_or("Insufficient balance")?;
+        self.ensure_can_withdraw(who, account.free, reasons)?;
+        if existence == ExistenceRequirement::KeepAlive && !Self::can_exist(&account) {
+            return Err("Account would be killed");
+        }
+        Ok(account)
+    }
+
+    fn can_exist(account: &AccountData<T::Balance>) -> bool {
+        account
+            .free
+            .checked_add(&account.reserved)
+            .is_none_or(|total| total >= T::EXISTENTIAL_DEPOSIT)
+    }
+
+    /// Store the balances of an account. An account left below the existential deposit is
+    /// removed along with its locks, and its remaining balance is burned.
+    fn write_account(&mut self, who: &T::AccountId, account: AccountData<T::Balance>) {
+        if Self::can_exist(&account) {
+            self.accounts.insert(who.clone(), account);
+            return;
+        }
+        if self.accounts.remove(who).is_none() {
+            return;
+        }
+        self.locks.remove(who);
+        self.reaped.push(who.clone());
+
+        // The total is below the existential deposit, so it can not overflow.
+        let dust = account.free + account.reserved;
+        self.total_issuance = self.total_issuance - dust;
+        if !dust.is_zero() {
+            self.deposit_event(Event::DustLost {
+                who: who.clone(),
+                amount: dust,
+            });
+        }
+    }
+
+    fn deposit_event(&mut self, event: Event<T>) {
+        self.events.push(event);
+    }
+
+    pub fn take_events(&mut self) -> Vec<Event<T>> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl<T: Config> Currency<T::AccountId> for Pallet<T> {
+    type Balance = T::Balance;
+
+    fn balance_of(&self, who: &T::AccountId) -> T::Balance {
+        self.balance(who)
+    }
+
+    fn total_issuance(&self) -> T::Balance {
+        self.total_issuance
+    }
+
+    fn can_withdraw(
+        &self,
+        who: &T::AccountId,
+        amount: T::Balance,
+        reasons: WithdrawReasons,
+        existence: ExistenceRequirement,
+    ) -> DispatchResult {
+        self.after_withdraw(who, amount, reasons, existence)
+            .map(|_| ())
+    }
+
+    fn transfer(
+        &mut self,
+        from: &T::AccountId,
+        to: &T::AccountId,
+        amount: T::Balance,
+        existence: ExistenceRequirement,
+    ) -> DispatchResult {
+        let from_account = self.after_withdraw(from, amount, WithdrawReasons::Misc, existence)?;
+
+        let mut to_account = self.account(to);
+        to_account.free = to_account
+            .free
+            .checked_add(&amount)
+            .ok_or("Overflow when adding to balance")?;
+        if !Self::can_exist(&to_account) {
+            return Err("Existential deposit not met");
+        }
+
+        // A transfer to oneself only has to pass the checks.
+        if from != to {
+            self.write_account(from, from_account);
+            self.write_account(to, to_account);
+        }
+
+        self.deposit_event(Event::Transfer {
+            from: from.clone(),
+            to: to.clone(),
+            amount,
+        });
+        Ok(())
+    }
+
+    fn withdraw(
+        &mut self,
+        who: &T::AccountId,
+        amount: T::Balance,
+        reasons: WithdrawReasons,
+        existence: ExistenceRequirement,
+    ) -> DispatchResult {
+        let account = self.after_withdraw(who, amount, reasons, existence)?;
+        // The balance of an account is part of the total issuance, so this can not underflow.
+        self.total_issuance = self.total_issuance - amount;
+        self.write_account(who, account);
+        Ok(())
+    }
+
+    fn deposit(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+        self.mint_into(who, amount)
+    }
+}
+
+impl<T: Config> ReservableCurrency<T::AccountId> for Pallet<T> {
+    fn reserved_balance(&self, who: &T::AccountId) -> T::Balance {
+        self.account(who).reserved
+    }
+
+    fn reserve(&mut self, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+        let mut account = self.account(who);
+        account.free = account
+            .free
+            .checked_sub(&amount)
+            .ok_or("Insufficient balance")?;
+        self.ensure_can_withdraw(who, account.free, WithdrawReasons::Misc)?;
+        account.reserved = account
+            .reserved
+            .checked_add(&amount)
+            .ok_or("Overflow when adding to reserved balance")?;
+        self.write_account(who, account);
+
+        self.deposit_event(Event::Reserved {
+            who: who.clone(),
+            amount,
+        });
+        Ok(())
+    }
+
+    fn unreserve(&mut self, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+        let mut account = self.account(who);
+        let actual = amount.min(account.reserved);
+        // The free balance can only overflow if the total issuance does, keep the funds reserved.
+        let Some(free) = account.free.checked_add(&actual) else {
+            return amount;
+        };
+        account.free = free;
+        account.reserved = account.reserved - actual;
+        self.write_account(who, account);
+
+        if !actual.is_zero() {
+            self.deposit_event(Event::Unreserved {
+                who: who.clone(),
+                amount: actual,
+            });
+        }
+        amount - actual
+    }
+
+    fn slash_reserved(&mut self, who: &T::AccountId, amount: T::Balance) -> T::Balance {
+        let mut account = self.account(who);
+        let actual = amount.min(account.reserved);
+        account.reserved = account.reserved - actual;
+        self.total_issuance = self.total_issuance - actual;
+        self.write_account(who, account);
+
+        if !actual.is_zero() {
+            self.deposit_event(Event::Slashed {
+                who: who.clone(),
+                amount: actual,
+            });
+        }
+        amount - actual
+    }
+
+    fn repatriate_reserved(
+        &mut self,
+        slashed: &T::AccountId,
+        beneficiary: &T::AccountId,
+        amount: T::Balance,
+    ) -> Result<T::Balance, DispatchError> {
+        if slashed == beneficiary {
+            return Ok(self.unreserve(slashed, amount));
+        }
+
+        let mut from = self.account(slashed);
+        let mut to = self.account(beneficiary);
+        let actual = amount.min(from.reserved);
+        from.reserved = from.reserved - actual;
+        to.free = to
+            .free
+            .checked_add(&actual)
+            .ok_or("Overflow when adding to balance")?;
+        // Like a transfer, this can't create an account below the existential deposit.
+        if !Self::can_exist(&to) {
+            return Err("Existential deposit not met");
+        }
+        self.write_account(slashed, from);
+        self.write_account(beneficiary, to);
+
+        if !actual.is_zero() {
+            self.deposit_event(Event::ReserveRepatriated {
+                from: slashed.clone(),
+                to: beneficiary.clone(),
+                amount: actual,
+            });
+        }
+        Ok(amount - actual)
+    }
+}
+
+impl<T: Config> LockableCurrency<T::AccountId> for Pallet<T> {
+    fn set_lock(
+        &mut self,
+        id: LockIdentifier,
+        who: &T::AccountId,
+        amount: T::Balance,
+        reasons: WithdrawReasons,
+    ) {
+        if amount.is_zero() {
+            return self.remove_lock(id, who);
+        }
+        let locks = self.locks.entry(who.clone()).or_default();
+        let lock = BalanceLock {
+            id,
+            amount,
+            reasons,
+        };
+        match locks.iter_mut().find(|lock| lock.id == id) {
+            Some(existing) => *existing = lock,
+            None => locks.push(lock),
+        }
+    }
+
+    fn extend_lock(
+        &mut self,
+        id: LockIdentifier,
+        who: &T::AccountId,
+        amount: T::Balance,
+        reasons: WithdrawReasons,
+    ) {
+        let (amount, reasons) = match self.locks(who).iter().find(|lock| lock.id == id) {
+            Some(existing) => (
+                amount.max(existing.amount),
+                if existing.reasons == reasons {
+                    reasons
+                } else {
+                    WithdrawReasons::All
+                },
+            ),
+            None => (amount, reasons),
+        };
+        self.set_lock(id, who, amount, reasons);
+    }
+
+    fn remove_lock(&mut self, id: LockIdentifier, who: &T::AccountId) {
+        if let Some(locks) = self.locks.get_mut(who) {
+            locks.retain(|lock| lock.id != id);
+            if locks.is_empty() {
+                self.locks.remove(who);
+            }
+        }
+    }
+}
+
+impl<T: Config> StorageSize for Pallet<T> {
+    fn storage_size(&self) -> usize {
+        self.accounts.len()
+            + self.locks.values().map(Vec::len).sum::<usize>()
+            + self.approvals.len()
+    }
+}
+
+impl<T: Config, Runtime> Hooks<Runtime> for Pallet<T>
+where
+    Runtime: AsMut<Pallet<T>> + AsMut<system::Pallet<T>>,
+{
+    // Reset the nonce of the accounts removed during the block, unless they were funded again.
+    fn on_finalize(runtime: &mut Runtime) {
+        let pallet: &mut Self = runtime.as_mut();
+        let mut reaped = std::mem::take(&mut pallet.reaped);
+        reaped.retain(|who| !pallet.accounts.contains_key(who));
+        let system: &mut system::Pallet<T> = runtime.as_mut();
+        for who in reaped {
+            system.remove_account(&who);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::support::{
+        Currency, ExistenceRequirement, Hooks, LockableCurrency, ReservableCurrency,
+        WithdrawReasons,
+    };
 
     struct TestConfig;
     impl crate::system::Config for TestConfig {
         type AccountId = String;
         type BlockNumber = u32;
         type Nonce = u32;
+        type RuntimeEvent = ();
     }
     impl super::Config for TestConfig {
         type Balance = u128;
+        const EXISTENTIAL_DEPOSIT: u128 = 10;
+    }
+
+    struct TestRuntime {
+        system: crate::system::Pallet<TestConfig>,
+        balances: super::Pallet<TestConfig>,
+    }
+
+    impl AsMut<crate::system::Pallet<TestConfig>> for TestRuntime {
+        fn as_mut(&mut self) -> &mut crate::system::Pallet<TestConfig> {
+            &mut self.system
+        }
+    }
+
+    impl AsMut<super::Pallet<TestConfig>> for TestRuntime {
+        fn as_mut(&mut self) -> &mut super::Pallet<TestConfig> {
+            &mut self.balances
+        }
     }
 
     #[test]
@@ -114,4 +738,221 @@ mod tests {
         assert_eq!(balances.balance(&"Alice".to_string()), 100);
         assert_eq!(balances.balance(&"Bob".to_string()), u128::MAX);
     }
+
+    #[test]
+    fn reserve_balance() {
+        let mut balances: super::Pallet<TestConfig> = super::Pallet::new();
+        let alice = "Alice".to_string();
+
+        balances.set_balance(&alice, 100);
+        assert_eq!(balances.reserve(&alice, 200), Err("Insufficient balance"));
+        assert_eq!(balances.reserve(&alice, 60), Ok(()));
+        assert_eq!(balances.balance(&alice), 40);
+        assert_eq!(balances.reserved_balance(&alice), 60);
+
+        // Reserved funds can not be transferred.
+        let result = balances.transfer(alice.clone(), "Bob".to_string(), 50);
+        assert_eq!(result, Err("Insufficient balance"));
+
+        // Only what is reserved can be unreserved, the rest is returned.
+        assert_eq!(balances.unreserve(&alice, 10), 0);
+        assert_eq!(balances.unreserve(&alice, 70), 20);
+        assert_eq!(balances.balance(&alice), 100);
+        assert_eq!(balances.reserved_balance(&alice), 0);
+    }
+
+    #[test]
+    fn slash_and_repatriate_reserved() {
+        let mut balances: super::Pallet<TestConfig> = super::Pallet::new();
+        let alice = "Alice".to_string();
+        let bob = "Bob".to_string();
+
+        balances.set_balance(&alice, 100);
+        balances.reserve(&alice, 80).unwrap();
+
+        assert_eq!(balances.slash_reserved(&alice, 30), 0);
+        assert_eq!(balances.reserved_balance(&alice), 50);
+
+        assert_eq!(balances.repatriate_reserved(&alice, &bob, 70), Ok(20));
+        assert_eq!(balances.reserved_balance(&alice), 0);
+        assert_eq!(balances.balance(&alice), 20);
+        assert_eq!(balances.balance(&bob), 50);
+        assert_eq!(balances.reserved_balance(&bob), 0);
+
+        // A new beneficiary must receive at least the existential deposit.
+        let charlie = "Charlie".to_string();
+        balances.reserve(&bob, 20).unwrap();
+        let res = balances.repatriate_reserved(&bob, &charlie, 5);
+        assert_eq!(res, Err("Existential deposit not met"));
+        assert_eq!(balances.reserved_balance(&bob), 20);
+        assert_eq!(balances.balance(&charlie), 0);
+        assert_eq!(balances.check_issuance(), Ok(()));
+    }
+
+    #[test]
+    fn lock_balance() {
+        let mut balances: super::Pallet<TestConfig> = super::Pallet::new();
+        let alice = "Alice".to_string();
+        let bob = "Bob".to_string();
+
+        balances.set_balance(&alice, 100);
+        balances.set_lock(*b"staking ", &alice, 50, WithdrawReasons::All);
+        balances.set_lock(*b"vesting ", &alice, 30, WithdrawReasons::Misc);
+
+        // Overlapping locks combine by maximum, so 50 can be spent.
+        let result = balances.transfer(alice.clone(), bob.clone(), 60);
+        assert_eq!(result, Err("Balance is locked"));
+        balances.transfer(alice.clone(), bob.clone(), 50).unwrap();
+        assert_eq!(balances.reserve(&alice, 1), Err("Balance is locked"));
+
+        // Extending never lowers a lock.
+        balances.extend_lock(*b"vesting ", &alice, 20, WithdrawReasons::Fee);
+        assert_eq!(balances.locks(&alice)[1].amount, 30);
+        assert_eq!(balances.locks(&alice)[1].reasons, WithdrawReasons::All);
+
+        balances.remove_lock(*b"staking ", &alice);
+        assert_eq!(balances.frozen_balance(&alice, WithdrawReasons::Misc), 30);
+        balances.transfer(alice.clone(), bob.clone(), 20).unwrap();
+        assert_eq!(balances.balance(&alice), 30);
+
+        balances.remove_lock(*b"vesting ", &alice);
+        assert!(balances.locks(&alice).is_empty());
+        balances.transfer(alice.clone(), bob.clone(), 30).unwrap();
+    }
+
+    #[test]
+    fn existential_deposit() {
+        let mut runtime = TestRuntime {
+            system: crate::system::Pallet::new(),
+            balances: super::Pallet::new(),
+        };
+        let alice = "Alice".to_string();
+        let bob = "Bob".to_string();
+
+        runtime.balances.set_balance(&alice, 100);
+        runtime.system.inc_nonce(&alice);
+
+        // New accounts must receive at least the existential deposit.
+        let result = runtime.balances.transfer(alice.clone(), bob.clone(), 5);
+        assert_eq!(result, Err("Existential deposit not met"));
+
+        // Keep-alive transfers can not leave the caller below the existential deposit.
+        let result = runtime
+            .balances
+            .transfer_keep_alive(alice.clone(), bob.clone(), 95);
+        assert_eq!(result, Err("Account would be killed"));
+        runtime
+            .balances
+            .transfer_keep_alive(alice.clone(), bob.clone(), 90)
+            .unwrap();
+
+        // Otherwise the caller is reaped, and the dust is burned.
+        runtime
+            .balances
+            .transfer(alice.clone(), bob.clone(), 5)
+            .unwrap();
+        assert_eq!(runtime.balances.balance(&alice), 0);
+        assert_eq!(runtime.balances.balance(&bob), 95);
+        assert!(!runtime.balances.accounts.contains_key(&alice));
+
+        // Its nonce is reset at the end of the block.
+        assert_eq!(runtime.system.nonce(&alice), 1);
+        super::Pallet::on_finalize(&mut runtime);
+        assert_eq!(runtime.system.nonce(&alice), 0);
+    }
+
+    #[test]
+    fn total_issuance() {
+        let mut balances: super::Pallet<TestConfig> = super::Pallet::new();
+        let alice = "Alice".to_string();
+        let bob = "Bob".to_string();
+
+        assert_eq!(
+            balances.mint_into(&alice, 5),
+            Err("Existential deposit not met")
+        );
+        assert_eq!(balances.deposit_creating(&alice, 5), 0);
+        assert_eq!(balances.deposit_creating(&alice, 100), 100);
+        balances.mint_into(&bob, 50).unwrap();
+        assert_eq!(balances.total_issuance(), 150);
+
+        balances.transfer(alice.clone(), bob.clone(), 30).unwrap();
+        balances.reserve(&bob, 40).unwrap();
+        balances.slash_reserved(&bob, 10);
+        assert_eq!(balances.total_issuance(), 140);
+
+        let result = balances.withdraw(
+            &alice,
+            65,
+            WithdrawReasons::Fee,
+            ExistenceRequirement::KeepAlive,
+        );
+        assert_eq!(result, Err("Account would be killed"));
+        balances.burn_from(&alice, 20).unwrap();
+        assert_eq!(balances.total_issuance(), 120);
+
+        // Burning down to dust reaps the account, and the dust is burned too.
+        balances.burn(alice.clone(), 45).unwrap();
+        assert_eq!(balances.balance(&alice), 0);
+        assert_eq!(balances.total_issuance(), 70);
+        assert_eq!(balances.check_issuance(), Ok(()));
+
+        balances.total_issuance = 71;
+        assert_eq!(
+            balances.check_issuance(),
+            Err("Total issuance does not match the balances")
+        );
+    }
+
+    #[test]
+    fn transfer_approved() {
+        let mut balances: super::Pallet<TestConfig> = super::Pallet::new();
+        let alice = "Alice".to_string();
+        let bob = "Bob".to_string();
+        let charlie = "Charlie".to_string();
+
+        balances.set_balance(&alice, 100);
+        let res = balances.transfer_approved(bob.clone(), alice.clone(), charlie.clone(), 10);
+        assert_eq!(res, Err("Allowance exceeded"));
+
+        balances
+            .approve_transfer(alice.clone(), bob.clone(), 50)
+            .unwrap();
+        balances
+            .transfer_approved(bob.clone(), alice.clone(), charlie.clone(), 30)
+            .unwrap();
+        assert_eq!(balances.balance(&alice), 70);
+        assert_eq!(balances.balance(&charlie), 30);
+        assert_eq!(balances.allowance(&alice, &bob), 20);
+
+        let res = balances.transfer_approved(bob.clone(), alice.clone(), charlie.clone(), 30);
+        assert_eq!(res, Err("Allowance exceeded"));
+
+        // A failed transfer does not spend the allowance.
+        balances.set_lock(*b"staking ", &alice, 60, WithdrawReasons::All);
+        let res = balances.transfer_approved(bob.clone(), alice.clone(), charlie.clone(), 20);
+        assert_eq!(res, Err("Balance is locked"));
+        assert_eq!(balances.allowance(&alice, &bob), 20);
+
+        balances
+            .cancel_approval(alice.clone(), bob.clone())
+            .unwrap();
+        assert_eq!(balances.allowance(&alice, &bob), 0);
+        let res = balances.cancel_approval(alice.clone(), bob.clone());
+        assert_eq!(res, Err("Approval does not exist"));
+
+        // Spending a whole allowance doesn't remove the account of the owner.
+        let dave = "Dave".to_string();
+        balances.set_balance(&dave, 50);
+        balances
+            .approve_transfer(dave.clone(), bob.clone(), 50)
+            .unwrap();
+        let res = balances.transfer_approved(bob.clone(), dave.clone(), charlie.clone(), 50);
+        assert_eq!(res, Err("Account would be killed"));
+        balances
+            .transfer_approved(bob.clone(), dave.clone(), charlie.clone(), 40)
+            .unwrap();
+        assert_eq!(balances.balance(&dave), 10);
+        assert_eq!(balances.allowance(&dave, &bob), 10);
+    }
 }
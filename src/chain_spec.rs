@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+
+use crate::json::{FromJson, ToJson, Value};
+use crate::{Runtime, types};
+
+/// The genesis state of a chain.
+pub struct ChainSpec {
+    pub name: String,
+    pub balances: BTreeMap<types::AccountId, types::Balance>,
+    /// The account allowed to make privileged calls, such as adding identity registrars.
+    pub root: Option<types::AccountId>,
+}
+
+impl ChainSpec {
+    /// A chain for local development, where Alice owns all the funds and is root.
+    pub fn development() -> Self {
+        Self {
+            name: "Development".to_string(),
+            balances: BTreeMap::from([("Alice".to_string(), 100)]),
+            root: Some("Alice".to_string()),
+        }
+    }
+
+    /// Build the runtime in its genesis state.
+    pub fn build_runtime(&self) -> Runtime {
+        let mut runtime = Runtime::new();
+        runtime.system.set_root(self.root.clone());
+        for (who, amount) in &self.balances {
+            runtime.balances.deposit_creating(who, *amount);
+        }
+        // The genesis state is not part of any block, so its events are dropped.
+        runtime.balances.take_events();
+        runtime
+    }
+}
+
+impl ToJson for ChainSpec {
+    fn to_json(&self) -> Value {
+        let balances = self
+            .balances
+            .iter()
+            .map(|(who, amount)| (who.clone(), amount.to_json()))
+            .collect();
+        Value::object([
+            ("name", self.name.to_json()),
+            ("balances", Value::Object(balances)),
+            ("root", self.root.to_json()),
+        ])
+    }
+}
+
+impl FromJson for ChainSpec {
+    fn from_json(value: &Value) -> Result<Self, &'static str> {
+        let balances = match value.field("balances")? {
+            Value::Object(balances) => balances
+                .iter()
+                .map(|(who, amount)| Ok((who.clone(), types::Balance::from_json(amount)?)))
+                .collect::<Result<_, &'static str>>()?,
+            _ => return Err("Expected an object of balances"),
+        };
+        Ok(Self {
+            name: String::from_json(value.field("name")?)?,
+            balances,
+            // Chain specs written before root existed have no root.
+            root: Option::from_json(value.get("root").unwrap_or(&Value::Null))?,
+        })
+    }
+}
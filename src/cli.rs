@@ -0,0 +1,294 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::chain_spec::ChainSpec;
+use crate::database::{self, Database};
+use crate::json::{ToJson, Value};
+use crate::log;
+use crate::node::Node;
+use crate::repl::Repl;
+use crate::{Runtime, proof_of_existence, rpc, types};
+
+const USAGE: &str = "\
+Usage: rsm <COMMAND> [OPTIONS]
+
+Commands:
+  run                    Start the node and its RPC server
+  build-spec             Write the development chain spec
+  import-blocks <FILE>   Import blocks from a file, one JSON block per line
+  export-blocks <FILE>   Export all blocks to a file, one JSON block per line
+  purge-chain            Remove the database
+  check-block <FILE>     Execute a JSON block on top of the chain, without storing it
+  verify-claim <FILE>    Check whether the hash of a file is claimed, and by whom
+  repl                   Start an interactive shell on a new in-memory chain
+
+Options:
+  --data-dir <DIR>       Where the database is stored [default: data]
+  --port <PORT>          The port of the RPC server [default: 9933]
+  --log-level <LEVEL>    One of error, warn, info or debug [default: info]
+  --chain <FILE>         The chain spec used when creating a new chain
+  --block-time <SECS>    The time between authored blocks [default: 6]
+  --output <FILE>        Where build-spec writes the chain spec [default: stdout]
+  --rpc-unsafe           Allow submitting extrinsics over RPC. Extrinsics are not signed, so
+                         any RPC client can then make calls as any account: never expose the
+                         port to untrusted clients";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run,
+    BuildSpec,
+    ImportBlocks(PathBuf),
+    ExportBlocks(PathBuf),
+    PurgeChain,
+    CheckBlock(PathBuf),
+    VerifyClaim(PathBuf),
+    Repl,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Cli {
+    pub command: Command,
+    pub data_dir: PathBuf,
+    pub port: u16,
+    pub log_level: log::Level,
+    pub chain: Option<PathBuf>,
+    pub block_time: Duration,
+    pub output: Option<PathBuf>,
+    pub rpc_unsafe: bool,
+}
+
+impl Cli {
+    /// Parse the command line arguments, not including the binary name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.into_iter();
+        let command = args.next().ok_or("Missing command")?;
+
+        let mut positional = Vec::new();
+        let mut cli = Cli {
+            command: Command::Run,
+            data_dir: PathBuf::from("data"),
+            port: 9933,
+            log_level: log::Level::Info,
+            chain: None,
+            block_time: Duration::from_secs(6),
+            output: None,
+            rpc_unsafe: false,
+        };
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                positional.push(arg);
+                continue;
+            }
+            if arg == "--rpc-unsafe" {
+                cli.rpc_unsafe = true;
+                continue;
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", arg))?;
+            match arg.as_str() {
+                "--data-dir" => cli.data_dir = PathBuf::from(value),
+                "--port" => cli.port = value.parse().map_err(|_| "Invalid port")?,
+                "--log-level" => {
+                    cli.log_level = log::Level::parse(&value).ok_or("Invalid log level")?
+                }
+                "--chain" => cli.chain = Some(PathBuf::from(value)),
+                "--block-time" => {
+                    let secs = value.parse().map_err(|_| "Invalid block time")?;
+                    cli.block_time = Duration::from_secs(secs);
+                }
+                "--output" => cli.output = Some(PathBuf::from(value)),
+                _ => return Err(format!("Unknown option {}", arg)),
+            }
+        }
+
+        let mut positional = positional.into_iter();
+        let mut file = || positional.next().map(PathBuf::from).ok_or("Missing file");
+        cli.command = match command.as_str() {
+            "run" => Command::Run,
+            "build-spec" => Command::BuildSpec,
+            "import-blocks" => Command::ImportBlocks(file()?),
+            "export-blocks" => Command::ExportBlocks(file()?),
+            "purge-chain" => Command::PurgeChain,
+            "check-block" => Command::CheckBlock(file()?),
+            "verify-claim" => Command::VerifyClaim(file()?),
+            "repl" => Command::Repl,
+            _ => return Err(format!("Unknown command {}", command)),
+        };
+        if let Some(arg) = positional.next() {
+            return Err(format!("Unexpected argument {}", arg));
+        }
+        Ok(cli)
+    }
+
+    /// The chain spec given with `--chain`, or the development chain spec.
+    fn chain_spec(&self) -> io::Result<ChainSpec> {
+        match &self.chain {
+            Some(path) => database::decode(&fs::read_to_string(path)?),
+            None => Ok(ChainSpec::development()),
+        }
+    }
+
+    /// Open the node in the data directory, creating the database from the chain spec if needed.
+    fn open_node(&self) -> io::Result<Node> {
+        let database = Database::open(&self.data_dir)?;
+        if database.chain_spec()?.is_none() {
+            let spec = self.chain_spec()?;
+            log::info!("Initializing the {} chain", spec.name);
+            database.set_chain_spec(&spec)?;
+        }
+        Node::open(database)
+    }
+
+    pub fn run(&self) -> Result<(), Box<dyn Error>> {
+        log::set_level(self.log_level);
+        match &self.command {
+            Command::Run => {
+                let node = self.open_node()?;
+                log::info!("Best block #{}", node.best_number());
+                let addr = ("127.0.0.1", self.port);
+                log::info!("RPC server listening on 127.0.0.1:{}", self.port);
+                if self.rpc_unsafe {
+                    log::warning!("Unsafe RPC methods are enabled, any client can submit calls");
+                }
+                rpc::serve(node, addr, self.block_time, self.rpc_unsafe)?;
+            }
+            Command::BuildSpec => {
+                let spec = ChainSpec::development().to_json().to_string();
+                match &self.output {
+                    Some(path) => fs::write(path, spec)?,
+                    None => println!("{}", spec),
+                }
+            }
+            Command::ImportBlocks(path) => {
+                let mut node = self.open_node()?;
+                let blocks = database::read_blocks(File::open(path)?)?;
+                let count = blocks.len();
+                for block in blocks {
+                    node.import_block(block)?;
+                }
+                log::info!(
+                    "Imported {} blocks, best block #{}",
+                    count,
+                    node.best_number()
+                );
+            }
+            Command::ExportBlocks(path) => {
+                let node = self.open_node()?;
+                database::write_blocks(File::create(path)?, node.blocks())?;
+                log::info!("Exported {} blocks", node.blocks().len());
+            }
+            Command::PurgeChain => {
+                Database::purge(&self.data_dir)?;
+                log::info!("Removed {}", self.data_dir.display());
+            }
+            Command::CheckBlock(path) => {
+                // The node is loaded without its database, so the block is never stored.
+                let mut node = Node::load(&Database::open(&self.data_dir)?)?;
+                let block: types::Block = database::decode(&fs::read_to_string(path)?)?;
+                let number = block.header.block_number;
+                node.import_block(block)?;
+                node.runtime().balances.check_issuance()?;
+                log::info!("Block #{} is valid", number);
+                println!("{}", node.receipt(number).to_json());
+            }
+            Command::VerifyClaim(path) => {
+                let node = Node::load(&Database::open(&self.data_dir)?)?;
+                let hash = proof_of_existence::Pallet::<Runtime>::hash_file(path)?;
+                let record = node.runtime().proof_of_existence.get_claim(&hash);
+                let verified_name =
+                    record.and_then(|record| node.runtime().identity.verified_name(&record.owner));
+                match record {
+                    Some(record) => log::info!(
+                        "{} is claimed by {}{} since block #{}",
+                        path.display(),
+                        record.owner,
+                        verified_name.map_or(String::new(), |name| format!(" ({})", name)),
+                        record.block_number
+                    ),
+                    None => log::info!("{} is not claimed", path.display()),
+                }
+                let claim = Value::object([
+                    ("hash", hash.to_json()),
+                    ("record", record.to_json()),
+                    ("verified_name", verified_name.to_json()),
+                ]);
+                println!("{}", claim);
+            }
+            Command::Repl => {
+                let runtime = self.chain_spec()?.build_runtime();
+                Repl::new(Node::new(runtime)).run()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parse the command line and run the command.
+pub fn run() {
+    let cli = match Cli::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = cli.run() {
+        log::error!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cli, Command};
+    use crate::log;
+    use std::path::PathBuf;
+
+    fn parse(args: &str) -> Result<Cli, String> {
+        Cli::parse(args.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn parse_commands() {
+        let cli = parse("run --port 9000 --data-dir /tmp/chain --log-level debug").unwrap();
+        assert_eq!(cli.command, Command::Run);
+        assert_eq!(cli.port, 9000);
+        assert_eq!(cli.data_dir, PathBuf::from("/tmp/chain"));
+        assert_eq!(cli.log_level, log::Level::Debug);
+        assert!(!cli.rpc_unsafe);
+        assert!(parse("run --rpc-unsafe --port 9000").unwrap().rpc_unsafe);
+
+        let cli = parse("import-blocks blocks.jsonl").unwrap();
+        assert_eq!(
+            cli.command,
+            Command::ImportBlocks(PathBuf::from("blocks.jsonl"))
+        );
+        assert_eq!(cli.port, 9933);
+
+        let cli = parse("verify-claim contract.pdf --data-dir /tmp/chain").unwrap();
+        assert_eq!(
+            cli.command,
+            Command::VerifyClaim(PathBuf::from("contract.pdf"))
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse(""), Err("Missing command".to_string()));
+        assert_eq!(parse("export-blocks"), Err("Missing file".to_string()));
+        assert_eq!(
+            parse("run --port"),
+            Err("Missing value for --port".to_string())
+        );
+        assert_eq!(parse("run --port x"), Err("Invalid port".to_string()));
+        assert_eq!(
+            parse("purge-chain x"),
+            Err("Unexpected argument x".to_string())
+        );
+        assert_eq!(parse("mine"), Err("Unknown command mine".to_string()));
+    }
+}
@@ -0,0 +1,124 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::chain_spec::ChainSpec;
+use crate::json::{FromJson, ToJson, Value};
+use crate::types;
+
+const CHAIN_SPEC_FILE: &str = "chain_spec.json";
+const BLOCKS_FILE: &str = "blocks.jsonl";
+
+/// The on-disk storage of a node: the chain spec it was started from and every imported block.
+///
+/// The state itself is not stored, it is rebuilt by replaying the blocks on top of the genesis
+/// state when the node starts.
+pub struct Database {
+    path: PathBuf,
+}
+
+impl Database {
+    /// Open the database in `path`, creating the directory if needed.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        fs::create_dir_all(&path)?;
+        Ok(Self { path })
+    }
+
+    /// Remove the database in `path` along with all of its blocks.
+    pub fn purge(path: &Path) -> io::Result<()> {
+        match fs::remove_dir_all(path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            res => res,
+        }
+    }
+
+    pub fn chain_spec(&self) -> io::Result<Option<ChainSpec>> {
+        match fs::read_to_string(self.path.join(CHAIN_SPEC_FILE)) {
+            Ok(spec) => decode(&spec).map(Some),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn set_chain_spec(&self, spec: &ChainSpec) -> io::Result<()> {
+        fs::write(self.path.join(CHAIN_SPEC_FILE), spec.to_json().to_string())
+    }
+
+    pub fn blocks(&self) -> io::Result<Vec<types::Block>> {
+        match File::open(self.path.join(BLOCKS_FILE)) {
+            Ok(file) => read_blocks(file),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn append_block(&self, block: &types::Block) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path.join(BLOCKS_FILE))?;
+        writeln!(file, "{}", block.to_json())
+    }
+}
+
+/// Read blocks encoded as one JSON object per line, as written by `write_blocks`.
+pub fn read_blocks(reader: impl io::Read) -> io::Result<Vec<types::Block>> {
+    let mut blocks = Vec::new();
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            blocks.push(decode(&line)?);
+        }
+    }
+    Ok(blocks)
+}
+
+pub fn write_blocks<'a>(
+    mut writer: impl Write,
+    blocks: impl IntoIterator<Item = &'a types::Block>,
+) -> io::Result<()> {
+    for block in blocks {
+        writeln!(writer, "{}", block.to_json())?;
+    }
+    Ok(())
+}
+
+/// Decode a value from its JSON text.
+pub fn decode<T: FromJson>(json: &str) -> io::Result<T> {
+    Value::parse(json)
+        .and_then(|value| T::from_json(&value))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Database;
+    use crate::chain_spec::ChainSpec;
+    use crate::{support, types};
+
+    #[test]
+    fn store_chain() {
+        let path = std::env::temp_dir().join(format!("rsm-db-test-{}", std::process::id()));
+        let db = Database::open(&path).unwrap();
+
+        assert!(db.chain_spec().unwrap().is_none());
+        assert!(db.blocks().unwrap().is_empty());
+
+        db.set_chain_spec(&ChainSpec::development()).unwrap();
+        let block = types::Block {
+            header: support::Header { block_number: 1 },
+            extrinsics: vec![],
+        };
+        db.append_block(&block).unwrap();
+        db.append_block(&block).unwrap();
+
+        let spec = db.chain_spec().unwrap().unwrap();
+        assert_eq!(spec.name, "Development");
+        assert_eq!(spec.balances.get("Alice"), Some(&100));
+        assert_eq!(db.blocks().unwrap().len(), 2);
+
+        Database::purge(&path).unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,483 @@
+use num::{CheckedAdd, CheckedMul, CheckedSub, Zero};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use crate::json::{FromJson, ToJson, Value};
+use crate::support::{DispatchError, DispatchResult, Hooks, ReservableCurrency, StorageSize};
+use crate::system;
+
+/// The longest a field of an identity can be, in bytes.
+const MAX_FIELD_LENGTH: usize = 64;
+
+/// Registrars are referred to by their position in the list of registrars.
+pub type RegistrarIndex = u32;
+
+pub trait Config: crate::system::Config {
+    type Balance: Debug + Zero + CheckedAdd + CheckedSub + CheckedMul + Ord + Copy + From<u32>;
+    /// Holds the deposits of identities.
+    type Currency: ReservableCurrency<Self::AccountId, Balance = Self::Balance>;
+    /// The deposit held for every identity.
+    const BASIC_DEPOSIT: Self::Balance;
+    /// The deposit held for every byte of the fields of an identity.
+    const DEPOSIT_PER_BYTE: Self::Balance;
+    /// Registrars are added by the root account of the system pallet, and deposits are held in
+    /// the currency.
+    type Runtime: AsMut<Pallet<Self>> + AsMut<system::Pallet<Self>> + AsMut<Self::Currency>;
+}
+
+/// What a registrar found when checking the fields of an identity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Judgement {
+    /// The fields look right, but were not checked in depth.
+    Reasonable,
+    /// The fields were checked, e.g. by contacting the email address.
+    KnownGood,
+    /// The fields are wrong.
+    Erroneous,
+}
+
+/// The information an account gives about itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdentityInfo {
+    pub display: String,
+    pub email: Option<String>,
+    pub web: Option<String>,
+}
+
+/// An identity, with the judgements of the registrars which checked it, sorted by registrar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Registration<Balance> {
+    pub info: IdentityInfo,
+    pub judgements: Vec<(RegistrarIndex, Judgement)>,
+    pub deposit: Balance,
+}
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+    identities: BTreeMap<T::AccountId, Registration<T::Balance>>,
+    registrars: Vec<T::AccountId>,
+    events: Vec<Event<T>>,
+}
+
+#[macros::event]
+pub enum Event<T: Config> {
+    RegistrarAdded {
+        registrar: T::AccountId,
+        index: RegistrarIndex,
+    },
+    IdentitySet {
+        who: T::AccountId,
+    },
+    IdentityCleared {
+        who: T::AccountId,
+        deposit: T::Balance,
+    },
+    JudgementGiven {
+        target: T::AccountId,
+        registrar_index: RegistrarIndex,
+        judgement: Judgement,
+    },
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Allow `account` to judge identities. Only root can add registrars.
+    pub fn add_registrar(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        account: T::AccountId,
+    ) -> DispatchResult {
+        let system: &mut system::Pallet<T> = runtime.as_mut();
+        system.ensure_root(&caller)?;
+
+        let pallet: &mut Self = runtime.as_mut();
+        if pallet.registrars.contains(&account) {
+            return Err("Already a registrar");
+        }
+        let index = pallet.registrars.len() as RegistrarIndex;
+        pallet.registrars.push(account.clone());
+        pallet.deposit_event(Event::RegistrarAdded {
+            registrar: account,
+            index,
+        });
+        Ok(())
+    }
+
+    /// Set the identity of the caller, replacing any previous one. A deposit is held for its
+    /// fields. The judgements of the previous identity no longer apply and are cleared, except
+    /// `Erroneous` ones, so that an account can't clear a flag by setting its identity again.
+    pub fn set_identity(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        display: String,
+        email: Option<String>,
+        web: Option<String>,
+    ) -> DispatchResult {
+        let info = IdentityInfo {
+            display,
+            email,
+            web,
+        };
+        let deposit = Self::deposit_for(&info)?;
+
+        let pallet: &mut Self = runtime.as_mut();
+        let (previous, judgements) = match pallet.identities.get(&caller) {
+            Some(registration) => {
+                let erroneous = registration
+                    .judgements
+                    .iter()
+                    .filter(|(_, judgement)| *judgement == Judgement::Erroneous)
+                    .copied()
+                    .collect();
+                (registration.deposit, erroneous)
+            }
+            None => (T::Balance::zero(), Vec::new()),
+        };
+        let currency: &mut T::Currency = runtime.as_mut();
+        if deposit > previous {
+            currency.reserve(&caller, deposit - previous)?;
+        } else {
+            currency.unreserve(&caller, previous - deposit);
+        }
+
+        let registration = Registration {
+            info,
+            judgements,
+            deposit,
+        };
+        let pallet: &mut Self = runtime.as_mut();
+        pallet.identities.insert(caller.clone(), registration);
+        pallet.deposit_event(Event::IdentitySet { who: caller });
+        Ok(())
+    }
+
+    /// Remove the identity of the caller, and return its deposit. An identity judged
+    /// `Erroneous` can't be cleared, so that its flag can't be dropped by clearing it and setting
+    /// a new one. The registrar has to change its judgement first.
+    pub fn clear_identity(runtime: &mut T::Runtime, caller: T::AccountId) -> DispatchResult {
+        let pallet: &mut Self = runtime.as_mut();
+        let registration = pallet.identities.get(&caller).ok_or("No identity")?;
+        if registration
+            .judgements
+            .iter()
+            .any(|(_, judgement)| *judgement == Judgement::Erroneous)
+        {
+            return Err("Identity judged erroneous");
+        }
+        let registration = pallet.identities.remove(&caller).ok_or("No identity")?;
+        let deposit = registration.deposit;
+        pallet.deposit_event(Event::IdentityCleared {
+            who: caller.clone(),
+            deposit,
+        });
+        let currency: &mut T::Currency = runtime.as_mut();
+        currency.unreserve(&caller, deposit);
+        Ok(())
+    }
+
+    /// Judge the identity of `target`, as the registrar at `registrar_index`. A new judgement of
+    /// the same registrar replaces its previous one.
+    pub fn provide_judgement(
+        &mut self,
+        caller: T::AccountId,
+        registrar_index: RegistrarIndex,
+        target: T::AccountId,
+        judgement: Judgement,
+    ) -> DispatchResult {
+        if self.registrars.get(registrar_index as usize) != Some(&caller) {
+            return Err("Caller is not the registrar");
+        }
+        let registration = self.identities.get_mut(&target).ok_or("No identity")?;
+
+        let judgements = &mut registration.judgements;
+        match judgements.binary_search_by_key(&registrar_index, |(index, _)| *index) {
+            Ok(i) => judgements[i].1 = judgement,
+            Err(i) => judgements.insert(i, (registrar_index, judgement)),
+        }
+        self.deposit_event(Event::JudgementGiven {
+            target,
+            registrar_index,
+            judgement,
+        });
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Self {
+            identities: BTreeMap::new(),
+            registrars: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn identity(&self, who: &T::AccountId) -> Option<&Registration<T::Balance>> {
+        self.identities.get(who)
+    }
+
+    pub fn registrars(&self) -> &[T::AccountId] {
+        &self.registrars
+    }
+
+    /// The display name of `who`, if at least one registrar judged its identity `Reasonable` or
+    /// `KnownGood`, and none judged it `Erroneous`.
+    pub fn verified_name(&self, who: &T::AccountId) -> Option<&str> {
+        let registration = self.identities.get(who)?;
+        let judgements = &registration.judgements;
+        let verified = !judgements.is_empty()
+            && judgements
+                .iter()
+                .all(|(_, judgement)| *judgement != Judgement::Erroneous);
+        verified.then_some(registration.info.display.as_str())
+    }
+
+    /// The deposit held for an identity with `info`.
+    fn deposit_for(info: &IdentityInfo) -> Result<T::Balance, DispatchError> {
+        let fields = [Some(&info.display), info.email.as_ref(), info.web.as_ref()];
+        let mut bytes = 0;
+        for field in fields.into_iter().flatten() {
+            if field.len() > MAX_FIELD_LENGTH {
+                return Err("Identity field too long");
+            }
+            bytes += field.len();
+        }
+        T::DEPOSIT_PER_BYTE
+            .checked_mul(&T::Balance::from(bytes as u32))
+            .and_then(|deposit| deposit.checked_add(&T::BASIC_DEPOSIT))
+            .ok_or("Deposit overflow")
+    }
+
+    fn deposit_event(&mut self, event: Event<T>) {
+        self.events.push(event);
+    }
+
+    pub fn take_events(&mut self) -> Vec<Event<T>> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl<T: Config> StorageSize for Pallet<T> {
+    fn storage_size(&self) -> usize {
+        self.identities.len() + self.registrars.len()
+    }
+}
+
+impl<T: Config, Runtime> Hooks<Runtime> for Pallet<T> {}
+
+impl ToJson for Judgement {
+    fn to_json(&self) -> Value {
+        let name = match self {
+            Judgement::Reasonable => "Reasonable",
+            Judgement::KnownGood => "KnownGood",
+            Judgement::Erroneous => "Erroneous",
+        };
+        name.to_json()
+    }
+}
+
+impl FromJson for Judgement {
+    fn from_json(value: &Value) -> Result<Self, &'static str> {
+        match value.as_str() {
+            Some("Reasonable") => Ok(Judgement::Reasonable),
+            Some("KnownGood") => Ok(Judgement::KnownGood),
+            Some("Erroneous") => Ok(Judgement::Erroneous),
+            _ => Err("Unknown judgement"),
+        }
+    }
+}
+
+impl ToJson for IdentityInfo {
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("display", self.display.to_json()),
+            ("email", self.email.to_json()),
+            ("web", self.web.to_json()),
+        ])
+    }
+}
+
+impl<Balance: ToJson> ToJson for Registration<Balance> {
+    fn to_json(&self) -> Value {
+        let judgements = self
+            .judgements
+            .iter()
+            .map(|(registrar_index, judgement)| {
+                Value::object([
+                    ("registrar_index", registrar_index.to_json()),
+                    ("judgement", judgement.to_json()),
+                ])
+            })
+            .collect();
+        Value::object([
+            ("info", self.info.to_json()),
+            ("judgements", Value::Array(judgements)),
+            ("deposit", self.deposit.to_json()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Judgement;
+    use crate::support::ReservableCurrency;
+    use crate::{balances, system};
+
+    struct TestConfig;
+    impl super::Config for TestConfig {
+        type Balance = u128;
+        type Currency = balances::Pallet<TestConfig>;
+        const BASIC_DEPOSIT: u128 = 10;
+        const DEPOSIT_PER_BYTE: u128 = 1;
+        type Runtime = TestRuntime;
+    }
+
+    impl balances::Config for TestConfig {
+        type Balance = u128;
+        const EXISTENTIAL_DEPOSIT: u128 = 1;
+    }
+
+    impl system::Config for TestConfig {
+        type AccountId = String;
+        type BlockNumber = u32;
+        type Nonce = u32;
+        type RuntimeEvent = ();
+    }
+
+    struct TestRuntime {
+        system: system::Pallet<TestConfig>,
+        balances: balances::Pallet<TestConfig>,
+        identity: super::Pallet<TestConfig>,
+    }
+
+    impl AsMut<system::Pallet<TestConfig>> for TestRuntime {
+        fn as_mut(&mut self) -> &mut system::Pallet<TestConfig> {
+            &mut self.system
+        }
+    }
+
+    impl AsMut<balances::Pallet<TestConfig>> for TestRuntime {
+        fn as_mut(&mut self) -> &mut balances::Pallet<TestConfig> {
+            &mut self.balances
+        }
+    }
+
+    impl AsMut<super::Pallet<TestConfig>> for TestRuntime {
+        fn as_mut(&mut self) -> &mut super::Pallet<TestConfig> {
+            &mut self.identity
+        }
+    }
+
+    type Identity = super::Pallet<TestConfig>;
+
+    fn new_runtime() -> TestRuntime {
+        let mut runtime = TestRuntime {
+            system: system::Pallet::new(),
+            balances: balances::Pallet::new(),
+            identity: Identity::new(),
+        };
+        runtime.system.set_root(Some("Alice".to_string()));
+        for who in ["Alice", "Bob"] {
+            runtime.balances.set_balance(&who.to_string(), 100);
+        }
+        runtime
+    }
+
+    #[test]
+    fn set_and_clear_identity() {
+        let mut runtime = new_runtime();
+        let bob = "Bob".to_string();
+
+        let email = Some("bob@example.com".to_string());
+        Identity::set_identity(&mut runtime, bob.clone(), "Bob".to_string(), email, None).unwrap();
+        assert_eq!(runtime.balances.reserved_balance(&bob), 10 + 3 + 15);
+        let identity = runtime.identity.identity(&bob).unwrap();
+        assert_eq!(identity.info.email.as_deref(), Some("bob@example.com"));
+
+        // Replacing the identity only holds the difference.
+        Identity::set_identity(&mut runtime, bob.clone(), "Bob".to_string(), None, None).unwrap();
+        assert_eq!(runtime.balances.reserved_balance(&bob), 10 + 3);
+
+        let display = "x".repeat(super::MAX_FIELD_LENGTH + 1);
+        let res = Identity::set_identity(&mut runtime, bob.clone(), display, None, None);
+        assert_eq!(res, Err("Identity field too long"));
+
+        Identity::clear_identity(&mut runtime, bob.clone()).unwrap();
+        assert_eq!(runtime.balances.reserved_balance(&bob), 0);
+        assert_eq!(runtime.identity.identity(&bob), None);
+        let res = Identity::clear_identity(&mut runtime, bob);
+        assert_eq!(res, Err("No identity"));
+    }
+
+    #[test]
+    fn judge_identities() {
+        let mut runtime = new_runtime();
+        let (alice, bob, charlie) = (
+            "Alice".to_string(),
+            "Bob".to_string(),
+            "Charlie".to_string(),
+        );
+
+        // Only root adds registrars.
+        let res = Identity::add_registrar(&mut runtime, bob.clone(), charlie.clone());
+        assert_eq!(res, Err("Caller is not root"));
+        Identity::add_registrar(&mut runtime, alice.clone(), charlie.clone()).unwrap();
+        Identity::add_registrar(&mut runtime, alice.clone(), alice.clone()).unwrap();
+        assert_eq!(
+            runtime.identity.registrars(),
+            [charlie.clone(), alice.clone()]
+        );
+
+        let res = runtime.identity.provide_judgement(
+            charlie.clone(),
+            0,
+            bob.clone(),
+            Judgement::KnownGood,
+        );
+        assert_eq!(res, Err("No identity"));
+        Identity::set_identity(&mut runtime, bob.clone(), "Bob".to_string(), None, None).unwrap();
+        assert_eq!(runtime.identity.verified_name(&bob), None);
+
+        let res = runtime.identity.provide_judgement(
+            charlie.clone(),
+            1,
+            bob.clone(),
+            Judgement::KnownGood,
+        );
+        assert_eq!(res, Err("Caller is not the registrar"));
+        runtime
+            .identity
+            .provide_judgement(charlie.clone(), 0, bob.clone(), Judgement::KnownGood)
+            .unwrap();
+        assert_eq!(runtime.identity.verified_name(&bob), Some("Bob"));
+
+        // A single erroneous judgement is enough to not be verified.
+        runtime
+            .identity
+            .provide_judgement(alice.clone(), 1, bob.clone(), Judgement::Erroneous)
+            .unwrap();
+        assert_eq!(runtime.identity.verified_name(&bob), None);
+
+        // Judgements don't apply to a new identity, except erroneous ones.
+        Identity::set_identity(&mut runtime, bob.clone(), "Robert".to_string(), None, None)
+            .unwrap();
+        let judgements = &runtime.identity.identity(&bob).unwrap().judgements;
+        assert_eq!(judgements, &vec![(1, Judgement::Erroneous)]);
+        runtime
+            .identity
+            .provide_judgement(charlie, 0, bob.clone(), Judgement::KnownGood)
+            .unwrap();
+        assert_eq!(runtime.identity.verified_name(&bob), None);
+
+        // Nor can they be dropped by clearing the identity and setting it again.
+        let res = Identity::clear_identity(&mut runtime, bob.clone());
+        assert_eq!(res, Err("Identity judged erroneous"));
+        runtime
+            .identity
+            .provide_judgement(alice, 1, bob.clone(), Judgement::Reasonable)
+            .unwrap();
+        Identity::clear_identity(&mut runtime, bob.clone()).unwrap();
+        Identity::set_identity(&mut runtime, bob.clone(), "Bob".to_string(), None, None).unwrap();
+        assert_eq!(runtime.identity.identity(&bob).unwrap().judgements, vec![]);
+    }
+}
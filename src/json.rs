@@ -0,0 +1,466 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A parsed JSON value.
+///
+/// Numbers are kept as their source text, so that `u128` balances can round trip without going
+/// through a lossy `f64`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Value>),
+    Object(BTreeMap<String, Value>),
+}
+
+/// Types which can be encoded as a JSON value.
+pub trait ToJson {
+    fn to_json(&self) -> Value;
+}
+
+/// Types which can be decoded from a JSON value.
+pub trait FromJson: Sized {
+    fn from_json(value: &Value) -> Result<Self, &'static str>;
+}
+
+impl Value {
+    pub fn parse(input: &str) -> Result<Self, &'static str> {
+        let mut parser = Parser {
+            input: input.as_bytes(),
+            pos: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos != parser.input.len() {
+            return Err("Trailing characters after JSON value");
+        }
+        Ok(value)
+    }
+
+    /// Build an object out of `(key, value)` pairs.
+    pub fn object<const N: usize>(fields: [(&str, Value); N]) -> Self {
+        Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields.get(key),
+            _ => None,
+        }
+    }
+
+    /// Get a field which must be present in an object.
+    pub fn field(&self, key: &str) -> Result<&Value, &'static str> {
+        self.get(key).ok_or("Missing field")
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Split an object with exactly one field into its key and value. This is how enum variants
+    /// are encoded, e.g. `{"transfer": {"to": "Bob", "amount": 30}}`.
+    pub fn as_variant(&self) -> Result<(&str, &Value), &'static str> {
+        match self {
+            Value::Object(fields) if fields.len() == 1 => {
+                let (key, value) = fields.iter().next().unwrap();
+                Ok((key, value))
+            }
+            _ => Err("Expected an object with a single field"),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write_string(f, s),
+            Value::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// How deeply arrays and objects can be nested. The parser recurses into them, so without a
+/// limit a long enough run of `[` would overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+    /// The number of arrays and objects the parser is currently inside of.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), &'static str> {
+        if self.peek() != Some(byte) {
+            return Err("Unexpected character");
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Value) -> Result<Value, &'static str> {
+        if !self.input[self.pos..].starts_with(keyword.as_bytes()) {
+            return Err("Unexpected character");
+        }
+        self.pos += keyword.len();
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, &'static str> {
+        self.skip_whitespace();
+        match self.peek().ok_or("Unexpected end of input")? {
+            b'n' => self.keyword("null", Value::Null),
+            b't' => self.keyword("true", Value::Bool(true)),
+            b'f' => self.keyword("false", Value::Bool(false)),
+            b'"' => Ok(Value::String(self.string()?)),
+            b'[' => self.nested(Self::array),
+            b'{' => self.nested(Self::object),
+            b'-' | b'0'..=b'9' => self.number(),
+            _ => Err("Unexpected character"),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Value, &'static str>,
+    ) -> Result<Value, &'static str> {
+        if self.depth == MAX_DEPTH {
+            return Err("JSON nested too deeply");
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn number(&mut self) -> Result<Value, &'static str> {
+        let start = self.pos;
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        while let Some(b'0'..=b'9' | b'.' | b'e' | b'E' | b'+' | b'-') = self.peek() {
+            self.pos += 1;
+        }
+        let text = std::str::from_utf8(&self.input[start..self.pos]).unwrap();
+        if text.parse::<f64>().is_err() {
+            return Err("Invalid number");
+        }
+        Ok(Value::Number(text.to_string()))
+    }
+
+    fn string(&mut self) -> Result<String, &'static str> {
+        self.expect(b'"')?;
+        let mut bytes = Vec::new();
+        loop {
+            let byte = self.peek().ok_or("Unterminated string")?;
+            self.pos += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = self.peek().ok_or("Unterminated string")?;
+                    self.pos += 1;
+                    let c = match escaped {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err("Invalid escape sequence"),
+                    };
+                    let mut buf = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                byte => bytes.push(byte),
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| "Invalid UTF-8 in string")
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, &'static str> {
+        let hex = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .ok_or("Invalid escape sequence")?;
+        self.pos += 4;
+        let code = std::str::from_utf8(hex)
+            .ok()
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .ok_or("Invalid escape sequence")?;
+        char::from_u32(code).ok_or("Invalid escape sequence")
+    }
+
+    fn array(&mut self) -> Result<Value, &'static str> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(items));
+                }
+                _ => return Err("Expected ',' or ']'"),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, &'static str> {
+        self.expect(b'{')?;
+        let mut fields = BTreeMap::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Value::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(b':')?;
+            let value = self.value()?;
+            fields.insert(key, value);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(fields));
+                }
+                _ => return Err("Expected ',' or '}'"),
+            }
+        }
+    }
+}
+
+macro_rules! impl_json_for_integer {
+    ($($t:ty),*) => {
+        $(
+            impl ToJson for $t {
+                fn to_json(&self) -> Value {
+                    Value::Number(self.to_string())
+                }
+            }
+
+            impl FromJson for $t {
+                fn from_json(value: &Value) -> Result<Self, &'static str> {
+                    match value {
+                        Value::Number(n) => n.parse().map_err(|_| "Invalid integer"),
+                        _ => Err("Expected a number"),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_json_for_integer!(u8, u16, u32, u64, u128, usize);
+
+impl ToJson for bool {
+    fn to_json(&self) -> Value {
+        Value::Bool(*self)
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &Value) -> Result<Self, &'static str> {
+        match value {
+            Value::Bool(b) => Ok(*b),
+            _ => Err("Expected a boolean"),
+        }
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> Value {
+        Value::String(self.clone())
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &Value) -> Result<Self, &'static str> {
+        value
+            .as_str()
+            .map(str::to_string)
+            .ok_or("Expected a string")
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> Value {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Value {
+        match self {
+            Some(value) => value.to_json(),
+            None => Value::Null,
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &Value) -> Result<Self, &'static str> {
+        match value {
+            Value::Null => Ok(None),
+            value => T::from_json(value).map(Some),
+        }
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn to_json(&self) -> Value {
+        (**self).to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+    fn from_json(value: &Value) -> Result<Self, &'static str> {
+        T::from_json(value).map(Box::new)
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &Value) -> Result<Self, &'static str> {
+        match value {
+            Value::Array(items) => items.iter().map(T::from_json).collect(),
+            _ => Err("Expected an array"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FromJson, ToJson, Value};
+
+    #[test]
+    fn parse_and_print() {
+        let input = r#"{"b": [1, true, null], "a": "x\"yA"}"#;
+        let value = Value::parse(input).unwrap();
+
+        assert_eq!(value.get("a"), Some(&Value::String("x\"yA".to_string())));
+        assert_eq!(value.to_string(), r#"{"a":"x\"yA","b":[1,true,null]}"#);
+        assert_eq!(Value::parse(&value.to_string()), Ok(value));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Value::parse("{\"a\": 1"), Err("Expected ',' or '}'"));
+        assert_eq!(
+            Value::parse("[1] 2"),
+            Err("Trailing characters after JSON value")
+        );
+        assert_eq!(Value::parse("\"abc"), Err("Unterminated string"));
+        assert_eq!(Value::parse("nul"), Err("Unexpected character"));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let nested = |depth| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(Value::parse(&nested(super::MAX_DEPTH)).is_ok());
+        assert_eq!(
+            Value::parse(&nested(super::MAX_DEPTH + 1)),
+            Err("JSON nested too deeply")
+        );
+        // Deep enough to overflow the stack without the limit.
+        let input = "[".repeat(200_000);
+        assert_eq!(Value::parse(&input), Err("JSON nested too deeply"));
+        let input = "{\"a\":".repeat(200_000);
+        assert_eq!(Value::parse(&input), Err("JSON nested too deeply"));
+    }
+
+    #[test]
+    fn large_numbers_round_trip() {
+        let value = u128::MAX.to_json();
+
+        assert_eq!(value.to_string(), u128::MAX.to_string());
+        assert_eq!(u128::from_json(&value), Ok(u128::MAX));
+        assert_eq!(u32::from_json(&value), Err("Invalid integer"));
+    }
+}
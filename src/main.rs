@@ -1,7 +1,22 @@
+mod assets;
 mod balances;
+mod chain_spec;
+mod cli;
+mod database;
+mod hashing;
+mod identity;
+mod json;
+mod log;
+mod multisig;
+mod node;
 mod proof_of_existence;
+mod proxy;
+mod repl;
+mod rpc;
 mod support;
 mod system;
+mod utility;
+mod vesting;
 mod types {
     use crate::support;
 
@@ -9,10 +24,12 @@ mod types {
     pub type Balance = u128;
     pub type BlockNumber = u32;
     pub type Nonce = u32;
+    pub type AssetId = u32;
     pub type Extrinsic = support::Extrinsic<AccountId, crate::RuntimeCall>;
     pub type Header = support::Header<BlockNumber>;
     pub type Block = support::Block<Header, Extrinsic>;
-    pub type Content = &'static str;
+    pub type Content = crate::hashing::H256;
+    pub type BlockReceipt = support::BlockReceipt<BlockNumber, Balance, crate::RuntimeEvent>;
 }
 
 use crate::support::Dispatch;
@@ -21,77 +38,137 @@ impl system::Config for Runtime {
     type AccountId = types::AccountId;
     type BlockNumber = types::BlockNumber;
     type Nonce = types::Nonce;
+    type RuntimeEvent = RuntimeEvent;
+}
+
+/// The fee paid for each unit of weight of an extrinsic.
+const FEE_PER_WEIGHT: types::Balance = 1;
+
+impl support::ChargeFee for Runtime {
+    type Caller = types::AccountId;
+    type Balance = types::Balance;
+
+    // The fee is withdrawn from the free balance of the caller and burned.
+    fn charge_fee(
+        &mut self,
+        who: &types::AccountId,
+        weight: support::Weight,
+    ) -> Result<types::Balance, support::DispatchError> {
+        let fee = types::Balance::from(weight) * FEE_PER_WEIGHT;
+        if support::Currency::balance_of(&self.balances, who) < fee {
+            return Err("Insufficient balance to pay the fee");
+        }
+        support::Currency::withdraw(
+            &mut self.balances,
+            who,
+            fee,
+            support::WithdrawReasons::Fee,
+            support::ExistenceRequirement::AllowDeath,
+        )?;
+        Ok(fee)
+    }
 }
 
 impl balances::Config for Runtime {
     type Balance = types::Balance;
+    const EXISTENTIAL_DEPOSIT: types::Balance = 1;
+}
+
+impl assets::Config for Runtime {
+    type AssetId = types::AssetId;
+    type Balance = types::Balance;
+}
+
+impl utility::Config for Runtime {
+    type RuntimeCall = RuntimeCall;
+    type Runtime = Runtime;
+}
+
+impl multisig::Config for Runtime {
+    type RuntimeCall = RuntimeCall;
+    type Balance = types::Balance;
+    type Currency = balances::Pallet<Runtime>;
+    const OPERATION_DEPOSIT: types::Balance = 1;
+    type Runtime = Runtime;
+}
+
+impl proxy::Config for Runtime {
+    type RuntimeCall = RuntimeCall;
+    type ProxyType = proxy::ProxyType;
+    type Runtime = Runtime;
+}
+
+impl proxy::InstanceFilter<RuntimeCall> for proxy::ProxyType {
+    fn filter(&self, call: &RuntimeCall) -> bool {
+        match (self, call) {
+            (proxy::ProxyType::Any, _) => true,
+            // The calls of a batch are dispatched as the real account, so each of them must pass.
+            (
+                _,
+                RuntimeCall::utility(
+                    utility::Call::batch { calls }
+                    | utility::Call::batch_all { calls }
+                    | utility::Call::force_batch { calls },
+                ),
+            ) => calls.iter().all(|call| self.filter(call)),
+            // Managing proxies is excluded, or the proxy could add itself as an `Any` proxy.
+            (proxy::ProxyType::NonTransfer, call) => !matches!(
+                call,
+                RuntimeCall::balances(_)
+                    | RuntimeCall::assets(assets::Call::transfer { .. })
+                    | RuntimeCall::vesting(vesting::Call::vested_transfer { .. })
+                    | RuntimeCall::proxy(_)
+            ),
+            (proxy::ProxyType::ClaimsOnly, call) => {
+                matches!(call, RuntimeCall::proof_of_existence(_))
+            }
+        }
+    }
+}
+
+impl vesting::Config for Runtime {
+    type Balance = types::Balance;
+    type Currency = balances::Pallet<Runtime>;
+    type Runtime = Runtime;
 }
 
 impl proof_of_existence::Config for Runtime {
     type Content = types::Content;
+
+    fn hash(data: &[u8]) -> types::Content {
+        hashing::sha256(data)
+    }
+
+    type Balance = types::Balance;
+    type Currency = balances::Pallet<Runtime>;
+    const CLAIM_DEPOSIT: types::Balance = 1;
+    const DEPOSIT_PER_BYTE: types::Balance = 0;
+    const MAX_CLAIMS_PER_ACCOUNT: usize = 1000;
+    type Runtime = Runtime;
+}
+
+impl identity::Config for Runtime {
+    type Balance = types::Balance;
+    type Currency = balances::Pallet<Runtime>;
+    const BASIC_DEPOSIT: types::Balance = 2;
+    const DEPOSIT_PER_BYTE: types::Balance = 0;
+    type Runtime = Runtime;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[macros::runtime]
 pub struct Runtime {
     system: system::Pallet<Runtime>,
     balances: balances::Pallet<Runtime>,
     proof_of_existence: proof_of_existence::Pallet<Runtime>,
+    assets: assets::Pallet<Runtime>,
+    utility: utility::Pallet<Runtime>,
+    vesting: vesting::Pallet<Runtime>,
+    multisig: multisig::Pallet<Runtime>,
+    proxy: proxy::Pallet<Runtime>,
+    identity: identity::Pallet<Runtime>,
 }
 
 fn main() {
-    let mut runtime = Runtime::new();
-
-    let alice = "Alice".to_string();
-    let bob = "Bob".to_string();
-    let charlie = "Charlie".to_string();
-
-    runtime.balances.set_balance(&alice, 100);
-
-    let block_1 = types::Block {
-        header: support::Header { block_number: 1 },
-        extrinsics: vec![
-            support::Extrinsic {
-                caller: alice.clone(),
-                call: RuntimeCall::balances(balances::Call::transfer {
-                    to: bob.clone(),
-                    amount: 30,
-                }),
-            },
-            support::Extrinsic {
-                caller: alice.clone(),
-                call: RuntimeCall::balances(balances::Call::transfer {
-                    to: charlie.clone(),
-                    amount: 20,
-                }),
-            },
-        ],
-    };
-
-    let block_2 = types::Block {
-        header: support::Header { block_number: 2 },
-        extrinsics: vec![
-            support::Extrinsic {
-                caller: alice.clone(),
-                call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
-                    claim: "my_document",
-                }),
-            },
-            support::Extrinsic {
-                caller: bob.clone(),
-                call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
-                    claim: "Bob's document",
-                }),
-            },
-        ],
-    };
-
-    runtime
-        .execute_block(block_1)
-        .expect("Wrong block execution!");
-    runtime
-        .execute_block(block_2)
-        .expect("Wrong block execution!");
-
-    println!("{:#?}", runtime);
+    cli::run();
 }
@@ -0,0 +1,417 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use crate::hashing::{H256, sha256};
+use crate::json::{ToJson, Value};
+use crate::support::{
+    ChargeFee, Dispatch, DispatchError, DispatchResult, GetWeight, Hooks, ReservableCurrency,
+    StorageSize,
+};
+
+/// The most signatories a multisig account can have.
+const MAX_SIGNATORIES: usize = 16;
+
+pub trait Config: crate::system::Config<AccountId: ToJson + From<String>> {
+    type RuntimeCall: GetWeight + ToJson;
+    type Balance: Debug + Copy;
+    /// Holds the deposits of pending operations.
+    type Currency: ReservableCurrency<Self::AccountId, Balance = Self::Balance>;
+    /// The deposit held from the signatory which opens an operation, until it is dispatched or
+    /// cancelled.
+    const OPERATION_DEPOSIT: Self::Balance;
+    /// Approved calls are dispatched through the runtime as the multisig account, and their fee
+    /// is charged through it when they are.
+    type Runtime: AsMut<Pallet<Self>>
+        + AsMut<Self::Currency>
+        + ChargeFee<Caller = Self::AccountId>
+        + Dispatch<Caller = Self::AccountId, Call = Self::RuntimeCall>;
+}
+
+/// A call waiting for the approvals of the signatories of a multisig account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Multisig<AccountId, Balance> {
+    /// The signatory which proposed the call, and the only one who can cancel it.
+    pub depositor: AccountId,
+    /// The deposit held from the depositor.
+    pub deposit: Balance,
+    /// The signatories which approved the call so far, sorted.
+    pub approvals: Vec<AccountId>,
+}
+
+/// Operations are keyed by the multisig account and the hash of their call.
+type OperationKey<T> = (<T as crate::system::Config>::AccountId, H256);
+type MultisigOf<T> = Multisig<<T as crate::system::Config>::AccountId, <T as Config>::Balance>;
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+    multisigs: BTreeMap<OperationKey<T>, MultisigOf<T>>,
+    events: Vec<Event<T>>,
+}
+
+#[macros::event]
+pub enum Event<T: Config> {
+    NewMultisig {
+        approving: T::AccountId,
+        multisig: T::AccountId,
+        call_hash: H256,
+    },
+    MultisigApproval {
+        approving: T::AccountId,
+        multisig: T::AccountId,
+        call_hash: H256,
+    },
+    /// The call reached its threshold and was dispatched, `error` is set if it failed.
+    MultisigExecuted {
+        approving: T::AccountId,
+        multisig: T::AccountId,
+        call_hash: H256,
+        error: Option<DispatchError>,
+    },
+    MultisigCancelled {
+        cancelling: T::AccountId,
+        multisig: T::AccountId,
+        call_hash: H256,
+    },
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Approve `call` for the multisig account of the caller and `other_signatories`, and dispatch
+    /// it as that account once `threshold` signatories approved it.
+    ///
+    /// Whether the call is dispatched is only known once the approvals are checked, so the weight
+    /// of `as_multi` is only that of an approval. The signatory whose approval dispatches the call
+    /// is charged for its weight then.
+    pub fn as_multi(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        threshold: u16,
+        other_signatories: Vec<T::AccountId>,
+        call: Box<T::RuntimeCall>,
+    ) -> DispatchResult {
+        let signatories = Self::signatories(&caller, threshold, other_signatories)?;
+        let multisig = Self::multi_account_id(&signatories, threshold);
+        let call_hash = Self::call_hash(&call);
+
+        let pallet: &mut Self = runtime.as_mut();
+        let key = (multisig.clone(), call_hash);
+        let mut approvals = 0;
+        if let Some(operation) = pallet.multisigs.get(&key) {
+            approvals = operation.approvals.len();
+            if operation.approvals.contains(&caller) {
+                approvals -= 1;
+            }
+        }
+        // The approval of the caller is the one which reaches the threshold.
+        if approvals + 1 < threshold as usize {
+            return Self::approve(runtime, caller, multisig, call_hash);
+        }
+
+        runtime.charge_fee(&caller, call.weight())?;
+        let pallet: &mut Self = runtime.as_mut();
+        if let Some(operation) = pallet.multisigs.remove(&key) {
+            let currency: &mut T::Currency = runtime.as_mut();
+            currency.unreserve(&operation.depositor, operation.deposit);
+        }
+        let error = runtime.dispatch(multisig.clone(), *call).err();
+        let pallet: &mut Self = runtime.as_mut();
+        pallet.deposit_event(Event::MultisigExecuted {
+            approving: caller,
+            multisig,
+            call_hash,
+            error,
+        });
+        Ok(())
+    }
+
+    /// Approve the call with `call_hash` without dispatching it. It is dispatched by the first
+    /// `as_multi` once it has enough approvals.
+    pub fn approve_as_multi(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        threshold: u16,
+        other_signatories: Vec<T::AccountId>,
+        call_hash: H256,
+    ) -> DispatchResult {
+        let signatories = Self::signatories(&caller, threshold, other_signatories)?;
+        let multisig = Self::multi_account_id(&signatories, threshold);
+        Self::approve(runtime, caller, multisig, call_hash)
+    }
+
+    /// Cancel a call which has not been dispatched yet, and return its deposit. Only the
+    /// signatory which proposed it can cancel it.
+    pub fn cancel_as_multi(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        threshold: u16,
+        other_signatories: Vec<T::AccountId>,
+        call_hash: H256,
+    ) -> DispatchResult {
+        let signatories = Self::signatories(&caller, threshold, other_signatories)?;
+        let multisig = Self::multi_account_id(&signatories, threshold);
+        let key = (multisig.clone(), call_hash);
+        let pallet: &mut Self = runtime.as_mut();
+        let operation = pallet
+            .multisigs
+            .get(&key)
+            .ok_or("Multisig operation not found")?;
+        if operation.depositor != caller {
+            return Err("Only the depositor can cancel");
+        }
+
+        let deposit = operation.deposit;
+        pallet.multisigs.remove(&key);
+        pallet.deposit_event(Event::MultisigCancelled {
+            cancelling: caller.clone(),
+            multisig,
+            call_hash,
+        });
+        let currency: &mut T::Currency = runtime.as_mut();
+        currency.unreserve(&caller, deposit);
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Self {
+            multisigs: BTreeMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// The account controlled by `signatories`, sorted, where `threshold` of them must approve a
+    /// call. The same set and threshold always give the same account.
+    pub fn multi_account_id(signatories: &[T::AccountId], threshold: u16) -> T::AccountId {
+        let id = Value::object([
+            (
+                "signatories",
+                Value::Array(signatories.iter().map(ToJson::to_json).collect()),
+            ),
+            ("threshold", threshold.to_json()),
+        ]);
+        T::AccountId::from(format!("Multisig-{}", sha256(id.to_string().as_bytes())))
+    }
+
+    pub fn call_hash(call: &T::RuntimeCall) -> H256 {
+        sha256(call.to_json().to_string().as_bytes())
+    }
+
+    pub fn multisig(&self, multisig: &T::AccountId, call_hash: H256) -> Option<&MultisigOf<T>> {
+        self.multisigs.get(&(multisig.clone(), call_hash))
+    }
+
+    /// Check the signatories of a multisig call, and return all of them sorted.
+    fn signatories(
+        caller: &T::AccountId,
+        threshold: u16,
+        other_signatories: Vec<T::AccountId>,
+    ) -> Result<Vec<T::AccountId>, DispatchError> {
+        let mut signatories = other_signatories;
+        signatories.push(caller.clone());
+        signatories.sort();
+        if signatories.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err("Signatories must be unique");
+        }
+        if signatories.len() > MAX_SIGNATORIES {
+            return Err("Too many signatories");
+        }
+        if threshold < 2 {
+            return Err("Threshold must be at least 2");
+        }
+        if threshold as usize > signatories.len() {
+            return Err("Threshold exceeds the number of signatories");
+        }
+        Ok(signatories)
+    }
+
+    /// Record the approval of `who`, proposing the call if nobody approved it yet. The signatory
+    /// which proposes it pays the deposit.
+    fn approve(
+        runtime: &mut T::Runtime,
+        who: T::AccountId,
+        multisig: T::AccountId,
+        call_hash: H256,
+    ) -> DispatchResult {
+        let key = (multisig.clone(), call_hash);
+        let pallet: &mut Self = runtime.as_mut();
+        let event = match pallet.multisigs.get_mut(&key) {
+            Some(operation) => {
+                let index = match operation.approvals.binary_search(&who) {
+                    Ok(_) => return Err("Already approved"),
+                    Err(index) => index,
+                };
+                operation.approvals.insert(index, who.clone());
+                Event::MultisigApproval {
+                    approving: who,
+                    multisig,
+                    call_hash,
+                }
+            }
+            None => {
+                let currency: &mut T::Currency = runtime.as_mut();
+                currency.reserve(&who, T::OPERATION_DEPOSIT)?;
+                let operation = Multisig {
+                    depositor: who.clone(),
+                    deposit: T::OPERATION_DEPOSIT,
+                    approvals: vec![who.clone()],
+                };
+                let pallet: &mut Self = runtime.as_mut();
+                pallet.multisigs.insert(key, operation);
+                Event::NewMultisig {
+                    approving: who,
+                    multisig,
+                    call_hash,
+                }
+            }
+        };
+        let pallet: &mut Self = runtime.as_mut();
+        pallet.deposit_event(event);
+        Ok(())
+    }
+
+    fn deposit_event(&mut self, event: Event<T>) {
+        self.events.push(event);
+    }
+
+    pub fn take_events(&mut self) -> Vec<Event<T>> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl<T: Config> StorageSize for Pallet<T> {
+    fn storage_size(&self) -> usize {
+        self.multisigs.len()
+    }
+}
+
+impl<T: Config, Runtime> Hooks<Runtime> for Pallet<T> {}
+
+impl<AccountId: ToJson, Balance: ToJson> ToJson for Multisig<AccountId, Balance> {
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("depositor", self.depositor.to_json()),
+            ("deposit", self.deposit.to_json()),
+            ("approvals", self.approvals.to_json()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chain_spec::ChainSpec;
+    use crate::support::{DEFAULT_WEIGHT, GetWeight, ReservableCurrency};
+    use crate::{Runtime, RuntimeCall, balances};
+
+    type Multisig = super::Pallet<Runtime>;
+
+    fn account(name: &str) -> String {
+        name.to_string()
+    }
+
+    fn transfer(to: &str, amount: u128) -> Box<RuntimeCall> {
+        Box::new(RuntimeCall::balances(balances::Call::transfer {
+            to: to.to_string(),
+            amount,
+        }))
+    }
+
+    #[test]
+    fn multi_account_id_is_deterministic() {
+        let signatories = vec![account("Alice"), account("Bob"), account("Charlie")];
+        let id = Multisig::multi_account_id(&signatories, 2);
+        assert_eq!(id, Multisig::multi_account_id(&signatories, 2));
+        assert_ne!(id, Multisig::multi_account_id(&signatories, 3));
+        assert!(id.starts_with("Multisig-0x"));
+    }
+
+    #[test]
+    fn dispatch_at_threshold() {
+        let mut runtime = ChainSpec::development().build_runtime();
+        let (alice, bob, charlie) = (account("Alice"), account("Bob"), account("Charlie"));
+        let multisig =
+            Multisig::multi_account_id(&[alice.clone(), bob.clone(), charlie.clone()], 2);
+        runtime
+            .balances
+            .transfer(alice.clone(), multisig.clone(), 50)
+            .unwrap();
+        runtime
+            .balances
+            .transfer(alice.clone(), bob.clone(), 10)
+            .unwrap();
+
+        // Approving only costs an approval, whatever the weight of the call.
+        let call = transfer("Dave", 20);
+        let call_hash = Multisig::call_hash(&call);
+        let others = vec![bob.clone(), charlie.clone()];
+        let as_multi = RuntimeCall::multisig(super::Call::as_multi {
+            threshold: 2,
+            other_signatories: others.clone(),
+            call: call.clone(),
+        });
+        assert_eq!(as_multi.weight(), DEFAULT_WEIGHT);
+
+        // The signatory which opens the operation pays its deposit.
+        Multisig::as_multi(&mut runtime, alice.clone(), 2, others, call.clone()).unwrap();
+        assert_eq!(runtime.balances.balance(&multisig), 50);
+        assert_eq!(runtime.balances.reserved_balance(&alice), 1);
+        let operation = runtime.multisig.multisig(&multisig, call_hash).unwrap();
+        assert_eq!(operation.approvals, vec![alice.clone()]);
+
+        let others = vec![bob.clone(), charlie.clone()];
+        let res = Multisig::as_multi(&mut runtime, alice.clone(), 2, others, call.clone());
+        assert_eq!(res, Err("Already approved"));
+
+        // The signatories can be given in any order. The approval which dispatches the call pays
+        // for its weight, and the deposit is returned.
+        let others = vec![charlie.clone(), alice.clone()];
+        Multisig::as_multi(&mut runtime, bob.clone(), 2, others, call.clone()).unwrap();
+        assert_eq!(runtime.balances.balance(&multisig), 30);
+        assert_eq!(runtime.balances.balance(&account("Dave")), 20);
+        assert_eq!(runtime.balances.balance(&bob), 10 - call.weight() as u128);
+        assert_eq!(runtime.balances.reserved_balance(&alice), 0);
+        assert_eq!(runtime.balances.balance(&alice), 40);
+        assert!(runtime.multisig.multisig(&multisig, call_hash).is_none());
+    }
+
+    #[test]
+    fn approve_and_cancel() {
+        let mut runtime = ChainSpec::development().build_runtime();
+        let (alice, bob, charlie) = (account("Alice"), account("Bob"), account("Charlie"));
+        let multisig =
+            Multisig::multi_account_id(&[alice.clone(), bob.clone(), charlie.clone()], 3);
+        let call_hash = Multisig::call_hash(&transfer("Dave", 20));
+
+        // Bob has no funds, so can't open the operation, but can still approve it.
+        let others = vec![alice.clone(), charlie.clone()];
+        let res = Multisig::approve_as_multi(&mut runtime, bob.clone(), 3, others, call_hash);
+        assert_eq!(res, Err("Insufficient balance"));
+        let others = vec![bob.clone(), charlie.clone()];
+        Multisig::approve_as_multi(&mut runtime, alice.clone(), 3, others, call_hash).unwrap();
+        assert_eq!(runtime.balances.reserved_balance(&alice), 1);
+        let others = vec![alice.clone(), charlie.clone()];
+        Multisig::approve_as_multi(&mut runtime, bob.clone(), 3, others.clone(), call_hash)
+            .unwrap();
+        let res = Multisig::cancel_as_multi(&mut runtime, bob.clone(), 3, others, call_hash);
+        assert_eq!(res, Err("Only the depositor can cancel"));
+
+        let others = vec![bob.clone(), charlie.clone()];
+        Multisig::cancel_as_multi(&mut runtime, alice.clone(), 3, others.clone(), call_hash)
+            .unwrap();
+        assert!(runtime.multisig.multisig(&multisig, call_hash).is_none());
+        assert_eq!(runtime.balances.reserved_balance(&alice), 0);
+        assert_eq!(runtime.balances.balance(&alice), 100);
+
+        let res = Multisig::as_multi(
+            &mut runtime,
+            alice.clone(),
+            1,
+            others.clone(),
+            transfer("Dave", 1),
+        );
+        assert_eq!(res, Err("Threshold must be at least 2"));
+        let others = vec![bob.clone(), alice.clone()];
+        let res = Multisig::as_multi(&mut runtime, alice.clone(), 2, others, transfer("Dave", 1));
+        assert_eq!(res, Err("Signatories must be unique"));
+    }
+}
@@ -0,0 +1,132 @@
+use std::io;
+
+use crate::database::Database;
+use crate::log;
+use crate::support::{self, DispatchResult};
+use crate::{Runtime, types};
+
+/// A single node of the chain: the runtime state, every block imported so far, and the pool of
+/// extrinsics waiting to be included in the next block.
+pub struct Node {
+    runtime: Runtime,
+    blocks: Vec<types::Block>,
+    receipts: Vec<types::BlockReceipt>,
+    pool: Vec<types::Extrinsic>,
+    database: Option<Database>,
+}
+
+impl Node {
+    /// Create a node which only keeps its chain in memory.
+    pub fn new(runtime: Runtime) -> Self {
+        Self {
+            runtime,
+            blocks: Vec::new(),
+            receipts: Vec::new(),
+            pool: Vec::new(),
+            database: None,
+        }
+    }
+
+    /// Open the node stored in `database`. Blocks imported by this node are written back to it.
+    pub fn open(database: Database) -> io::Result<Self> {
+        let mut node = Self::load(&database)?;
+        node.database = Some(database);
+        Ok(node)
+    }
+
+    /// Rebuild the chain stored in `database` in memory, by replaying all of its blocks on top of
+    /// the genesis state. Blocks imported by this node are not written back.
+    pub fn load(database: &Database) -> io::Result<Self> {
+        let spec = database
+            .chain_spec()?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Missing chain spec"))?;
+        let mut node = Self::new(spec.build_runtime());
+        for block in database.blocks()? {
+            node.import_block(block).map_err(io::Error::other)?;
+        }
+        Ok(node)
+    }
+
+    pub fn runtime(&self) -> &Runtime {
+        &self.runtime
+    }
+
+    /// The number of the latest imported block, or zero if no block was imported yet.
+    pub fn best_number(&self) -> types::BlockNumber {
+        self.runtime.system.block_number()
+    }
+
+    pub fn blocks(&self) -> &[types::Block] {
+        &self.blocks
+    }
+
+    pub fn block(&self, number: types::BlockNumber) -> Option<&types::Block> {
+        self.blocks
+            .iter()
+            .find(|block| block.header.block_number == number)
+    }
+
+    /// The outcome of each extrinsic of a block.
+    pub fn receipt(&self, number: types::BlockNumber) -> Option<&types::BlockReceipt> {
+        self.receipts
+            .iter()
+            .find(|receipt| receipt.block_number == number)
+    }
+
+    /// Add an extrinsic to the pool. It will be included in the next authored block.
+    pub fn submit_extrinsic(&mut self, extrinsic: types::Extrinsic) {
+        self.pool.push(extrinsic);
+    }
+
+    pub fn pending_extrinsics(&self) -> usize {
+        self.pool.len()
+    }
+
+    /// Execute a block on top of the current state and store it.
+    ///
+    /// The block runs on a copy of the state, which only replaces the current one once the block
+    /// is written to the database. If anything fails, the node is left as it was.
+    pub fn import_block(&mut self, block: types::Block) -> DispatchResult {
+        let mut runtime = self.runtime.clone();
+        let receipt = runtime.execute_block(block.clone())?;
+        if let Some(database) = &self.database {
+            database
+                .append_block(&block)
+                .map_err(|_| "Failed to write the block to the database")?;
+        }
+        self.runtime = runtime;
+        log::debug!(
+            "Imported block #{} ({} extrinsics)",
+            block.header.block_number,
+            block.extrinsics.len()
+        );
+        for (i, extrinsic) in receipt.extrinsics.iter().enumerate() {
+            if let Err(e) = extrinsic.result {
+                log::warning!(
+                    "Extrinsic #{} of block #{} failed: {}",
+                    i,
+                    receipt.block_number,
+                    e
+                );
+            }
+        }
+        self.blocks.push(block);
+        self.receipts.push(receipt);
+        Ok(())
+    }
+
+    /// Build a block out of every extrinsic in the pool and import it. The pool is only emptied
+    /// once the block is executed and stored, so its extrinsics are kept, and not yet applied, if
+    /// the import fails.
+    pub fn author_block(&mut self) -> DispatchResult {
+        let block = types::Block {
+            header: support::Header {
+                block_number: self.best_number() + 1,
+            },
+            extrinsics: self.pool.clone(),
+        };
+        self.import_block(block)?;
+        self.pool.clear();
+        Ok(())
+    }
+}
@@ -1,89 +1,1252 @@
-use std::collections::BTreeMap;
+use num::{CheckedAdd, CheckedMul, One, Zero};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Debug;
+use std::ops::Bound;
+use std::path::Path;
+use std::{fs, io};
 
-use crate::support::DispatchResult;
+use crate::json::{ToJson, Value};
+use crate::support::{DispatchError, DispatchResult, Hooks, ReservableCurrency, StorageSize};
+use crate::system;
 
-pub trait Config: crate::system::Config {
-    type Content: Debug + Ord;
+/// The longest description a claim can have, in bytes.
+const DESCRIPTION_LIMIT: usize = 256;
+/// The most signers a joint claim can require.
+const MAX_SIGNERS: usize = 16;
+
+pub trait Config: crate::system::Config<BlockNumber: Ord + CheckedAdd> {
+    /// A fixed size hash of a document, so that only the hash goes on-chain. Its bytes are
+    /// hashed again to build the Merkle trees of batch claims.
+    type Content: Debug + Ord + Clone + AsRef<[u8]>;
+    /// Hash a document into its `Content`. This happens off-chain, before claiming it.
+    fn hash(data: &[u8]) -> Self::Content;
+    /// The balance of the currency the deposits are held in.
+    type Balance: Debug + Zero + CheckedAdd + CheckedMul + Copy + From<u32>;
+    /// Holds the deposits of claims.
+    type Currency: ReservableCurrency<Self::AccountId, Balance = Self::Balance>;
+    /// The deposit held for every claim, and for every batch of claims.
+    const CLAIM_DEPOSIT: Self::Balance;
+    /// The deposit held for every byte of the description of a claim.
+    const DEPOSIT_PER_BYTE: Self::Balance;
+    /// The most claims an account can own at once. A batch of claims counts as one.
+    const MAX_CLAIMS_PER_ACCOUNT: usize;
+    /// Claims record the block number of the system pallet they were created at, and hold their
+    /// deposit in the currency.
+    type Runtime: AsMut<Pallet<Self>> + AsMut<system::Pallet<Self>> + AsMut<Self::Currency>;
+}
+
+/// Everything known about a claim. The `block_number` proves the document existed at that block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClaimRecord<AccountId, BlockNumber, Balance> {
+    pub owner: AccountId,
+    pub block_number: BlockNumber,
+    /// When the owner says the document was made, in milliseconds since the Unix epoch. It is
+    /// not checked by the chain.
+    pub timestamp: Option<u64>,
+    /// A description of the document, or a URI where it can be found.
+    pub description: Option<String>,
+    /// The owners the claim was transferred from, oldest first.
+    pub previous_owners: Vec<AccountId>,
+    /// The account the owner offered the claim to, until it accepts it.
+    pub pending_owner: Option<AccountId>,
+    /// The block from which the claim is no longer valid, if any.
+    pub expires_at: Option<BlockNumber>,
+    /// The funds reserved from the owner while the claim exists.
+    pub deposit: Balance,
+    /// The accounts which cosigned a joint claim, sorted. Empty for claims made by the owner alone.
+    pub attesters: Vec<AccountId>,
+}
+
+pub type ClaimRecordOf<T> = ClaimRecord<
+    <T as system::Config>::AccountId,
+    <T as system::Config>::BlockNumber,
+    <T as Config>::Balance,
+>;
+
+/// A claim proposed by `owner` which only takes effect once every required signer cosigned it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JointClaim<AccountId, Balance> {
+    pub owner: AccountId,
+    /// The accounts which must cosign the claim, sorted.
+    pub required_signers: Vec<AccountId>,
+    /// The required signers which cosigned so far, sorted.
+    pub cosigned: Vec<AccountId>,
+    pub deposit: Balance,
+}
+
+pub type JointClaimOf<T> = JointClaim<<T as system::Config>::AccountId, <T as Config>::Balance>;
+
+/// A batch of documents claimed at once through the Merkle root of their hashes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchRecord<AccountId, BlockNumber, Balance> {
+    pub owner: AccountId,
+    pub block_number: BlockNumber,
+    /// The number of documents in the batch, which sets the length of their Merkle paths.
+    pub count: u32,
+    pub deposit: Balance,
 }
 
-#[derive(Debug)]
+pub type BatchRecordOf<T> = BatchRecord<
+    <T as system::Config>::AccountId,
+    <T as system::Config>::BlockNumber,
+    <T as Config>::Balance,
+>;
+
+#[derive(Debug, Clone)]
 pub struct Pallet<T: Config> {
-    claims: BTreeMap<T::Content, T::AccountId>,
+    claims: BTreeMap<T::Content, ClaimRecordOf<T>>,
+    /// The claims of each owner, kept in sync with `claims` so they can be listed.
+    owned: BTreeMap<T::AccountId, BTreeSet<T::Content>>,
+    /// Joint claims waiting for their signers.
+    joint_claims: BTreeMap<T::Content, JointClaimOf<T>>,
+    /// Batches keyed by their Merkle root.
+    batches: BTreeMap<T::Content, BatchRecordOf<T>>,
+    /// The claims which expire at each block, so they can be pruned without scanning `claims`.
+    expiries: BTreeMap<T::BlockNumber, Vec<T::Content>>,
+    events: Vec<Event<T>>,
+}
+
+#[macros::event]
+pub enum Event<T: Config> {
+    ClaimCreated {
+        owner: T::AccountId,
+        claim: T::Content,
+    },
+    ClaimRevoked {
+        owner: T::AccountId,
+        claim: T::Content,
+    },
+    TransferProposed {
+        owner: T::AccountId,
+        new_owner: T::AccountId,
+        claim: T::Content,
+    },
+    ClaimTransferred {
+        from: T::AccountId,
+        to: T::AccountId,
+        claim: T::Content,
+    },
+    ClaimRenewed {
+        owner: T::AccountId,
+        claim: T::Content,
+        expires_at: Option<T::BlockNumber>,
+    },
+    ClaimExpired {
+        owner: T::AccountId,
+        claim: T::Content,
+    },
+    JointClaimProposed {
+        owner: T::AccountId,
+        claim: T::Content,
+    },
+    JointClaimCosigned {
+        signer: T::AccountId,
+        claim: T::Content,
+    },
+    JointClaimCancelled {
+        owner: T::AccountId,
+        claim: T::Content,
+    },
+    BatchCreated {
+        owner: T::AccountId,
+        root: T::Content,
+        count: u32,
+    },
+    BatchRevoked {
+        owner: T::AccountId,
+        root: T::Content,
+    },
 }
 
 #[macros::call]
 impl<T: Config> Pallet<T> {
-    pub fn create_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
-        match self.get_claim(&claim) {
-            Some(_) => Err("Claim already exists"),
-            None => {
-                self.claims.insert(claim, caller);
-                Ok(())
-            }
+    pub fn create_claim(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        claim: T::Content,
+        description: Option<String>,
+        timestamp: Option<u64>,
+        expires_at: Option<T::BlockNumber>,
+    ) -> DispatchResult {
+        if description
+            .as_ref()
+            .is_some_and(|d| d.len() > DESCRIPTION_LIMIT)
+        {
+            return Err("Description too long");
+        }
+        let system: &mut system::Pallet<T> = runtime.as_mut();
+        let block_number = system.block_number();
+        if expires_at.is_some_and(|expires_at| expires_at <= block_number) {
+            return Err("Expiry must be in the future");
+        }
+
+        let pallet: &mut Self = runtime.as_mut();
+        pallet.ensure_unclaimed(&claim)?;
+        pallet.ensure_can_own(&caller)?;
+        let deposit = Self::deposit_for(description.as_deref())?;
+        let currency: &mut T::Currency = runtime.as_mut();
+        currency.reserve(&caller, deposit)?;
+
+        let pallet: &mut Self = runtime.as_mut();
+        let record = ClaimRecord {
+            owner: caller.clone(),
+            block_number,
+            timestamp,
+            description,
+            previous_owners: Vec::new(),
+            pending_owner: None,
+            expires_at,
+            deposit,
+            attesters: Vec::new(),
+        };
+        pallet.claims.insert(claim.clone(), record);
+        pallet.add_owned(&caller, &claim);
+        if let Some(expires_at) = expires_at {
+            pallet
+                .expiries
+                .entry(expires_at)
+                .or_default()
+                .push(claim.clone());
+        }
+        pallet.deposit_event(Event::ClaimCreated {
+            owner: caller,
+            claim,
+        });
+        Ok(())
+    }
+
+    /// Remove a claim of the caller, and return its deposit.
+    pub fn revoke_claim(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        claim: T::Content,
+    ) -> DispatchResult {
+        let pallet: &mut Self = runtime.as_mut();
+        let record = pallet.get_claim(&claim).ok_or("Claim does not exist")?;
+
+        if record.owner != caller {
+            return Err("Caller is not the owner of the claim");
+        }
+
+        let (expires_at, deposit) = (record.expires_at, record.deposit);
+        pallet.claims.remove(&claim);
+        pallet.remove_owned(&caller, &claim);
+        pallet.remove_expiry(&claim, expires_at);
+        pallet.deposit_event(Event::ClaimRevoked {
+            owner: caller.clone(),
+            claim,
+        });
+        let currency: &mut T::Currency = runtime.as_mut();
+        currency.unreserve(&caller, deposit);
+        Ok(())
+    }
+
+    /// Offer a claim to `new_owner`. It only changes hands once `new_owner` accepts it, and
+    /// offering it again replaces the previous offer.
+    pub fn transfer_claim(
+        &mut self,
+        caller: T::AccountId,
+        claim: T::Content,
+        new_owner: T::AccountId,
+    ) -> DispatchResult {
+        let record = self.claims.get_mut(&claim).ok_or("Claim does not exist")?;
+        if record.owner != caller {
+            return Err("Caller is not the owner of the claim");
+        }
+        if new_owner == caller {
+            return Err("Cannot transfer a claim to its owner");
+        }
+
+        record.pending_owner = Some(new_owner.clone());
+        self.deposit_event(Event::TransferProposed {
+            owner: caller,
+            new_owner,
+            claim,
+        });
+        Ok(())
+    }
+
+    /// Accept a claim offered to the caller with `transfer_claim`. The claim keeps its creation
+    /// block, and the previous owner is added to its history. The deposit is now held from the
+    /// caller, and returned to the previous owner.
+    pub fn accept_claim(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        claim: T::Content,
+    ) -> DispatchResult {
+        let pallet: &mut Self = runtime.as_mut();
+        let record = pallet.get_claim(&claim).ok_or("Claim does not exist")?;
+        if record.pending_owner.as_ref() != Some(&caller) {
+            return Err("Claim was not offered to the caller");
+        }
+        let (from, deposit) = (record.owner.clone(), record.deposit);
+        pallet.ensure_can_own(&caller)?;
+        let currency: &mut T::Currency = runtime.as_mut();
+        currency.reserve(&caller, deposit)?;
+        currency.unreserve(&from, deposit);
+
+        let pallet: &mut Self = runtime.as_mut();
+        let record = pallet
+            .claims
+            .get_mut(&claim)
+            .ok_or("Claim does not exist")?;
+        record.pending_owner = None;
+        record.owner = caller.clone();
+        record.previous_owners.push(from.clone());
+        pallet.remove_owned(&from, &claim);
+        pallet.add_owned(&caller, &claim);
+        pallet.deposit_event(Event::ClaimTransferred {
+            from,
+            to: caller,
+            claim,
+        });
+        Ok(())
+    }
+
+    /// Move the expiry of a claim to `expires_at`, or remove it with `None`.
+    pub fn renew_claim(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        claim: T::Content,
+        expires_at: Option<T::BlockNumber>,
+    ) -> DispatchResult {
+        let system: &mut system::Pallet<T> = runtime.as_mut();
+        if expires_at.is_some_and(|expires_at| expires_at <= system.block_number()) {
+            return Err("Expiry must be in the future");
+        }
+
+        let pallet: &mut Self = runtime.as_mut();
+        let record = pallet
+            .claims
+            .get_mut(&claim)
+            .ok_or("Claim does not exist")?;
+        if record.owner != caller {
+            return Err("Caller is not the owner of the claim");
+        }
+
+        let previous = std::mem::replace(&mut record.expires_at, expires_at);
+        pallet.remove_expiry(&claim, previous);
+        if let Some(expires_at) = expires_at {
+            pallet
+                .expiries
+                .entry(expires_at)
+                .or_default()
+                .push(claim.clone());
+        }
+        pallet.deposit_event(Event::ClaimRenewed {
+            owner: caller,
+            claim,
+            expires_at,
+        });
+        Ok(())
+    }
+
+    /// Propose a claim which is only created once every one of `required_signers` cosigned it
+    /// with `cosign_claim`. The deposit is held from the caller, who owns the claim.
+    pub fn propose_joint_claim(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        claim: T::Content,
+        required_signers: Vec<T::AccountId>,
+    ) -> DispatchResult {
+        let mut required_signers = required_signers;
+        required_signers.sort();
+        if required_signers.is_empty() {
+            return Err("Joint claim needs signers");
         }
+        if required_signers.len() > MAX_SIGNERS {
+            return Err("Too many signers");
+        }
+        if required_signers.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err("Signers must be unique");
+        }
+        let pallet: &mut Self = runtime.as_mut();
+        pallet.ensure_unclaimed(&claim)?;
+        pallet.ensure_can_own(&caller)?;
+        let currency: &mut T::Currency = runtime.as_mut();
+        currency.reserve(&caller, T::CLAIM_DEPOSIT)?;
+
+        let joint_claim = JointClaim {
+            owner: caller.clone(),
+            required_signers,
+            cosigned: Vec::new(),
+            deposit: T::CLAIM_DEPOSIT,
+        };
+        let pallet: &mut Self = runtime.as_mut();
+        pallet.joint_claims.insert(claim.clone(), joint_claim);
+        pallet.deposit_event(Event::JointClaimProposed {
+            owner: caller,
+            claim,
+        });
+        Ok(())
     }
 
-    pub fn revoke_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
-        let claim_owner = self.get_claim(&claim).ok_or("Claim does not exist")?;
+    /// Attest a joint claim the caller is a required signer of. The last signature creates the
+    /// claim, at the current block and with all the signers as its attesters.
+    pub fn cosign_claim(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        claim: T::Content,
+    ) -> DispatchResult {
+        let system: &mut system::Pallet<T> = runtime.as_mut();
+        let block_number = system.block_number();
+
+        let pallet: &mut Self = runtime.as_mut();
+        let joint_claim = pallet
+            .get_joint_claim(&claim)
+            .ok_or("Joint claim does not exist")?;
+        if joint_claim.required_signers.binary_search(&caller).is_err() {
+            return Err("Caller is not a required signer");
+        }
+        let index = match joint_claim.cosigned.binary_search(&caller) {
+            Ok(_) => return Err("Already cosigned"),
+            Err(index) => index,
+        };
+        let complete = joint_claim.cosigned.len() + 1 == joint_claim.required_signers.len();
+        let owner = joint_claim.owner.clone();
+        if complete {
+            // The owner may have claimed other documents since proposing this one.
+            pallet.ensure_can_own(&owner)?;
+        }
+
+        let joint_claim = pallet
+            .joint_claims
+            .get_mut(&claim)
+            .ok_or("Joint claim does not exist")?;
+        joint_claim.cosigned.insert(index, caller.clone());
+        pallet.deposit_event(Event::JointClaimCosigned {
+            signer: caller,
+            claim: claim.clone(),
+        });
+        if !complete {
+            return Ok(());
+        }
 
-        if claim_owner != &caller {
+        let joint_claim = pallet
+            .joint_claims
+            .remove(&claim)
+            .ok_or("Joint claim does not exist")?;
+        let record = ClaimRecord {
+            owner: owner.clone(),
+            block_number,
+            timestamp: None,
+            description: None,
+            previous_owners: Vec::new(),
+            pending_owner: None,
+            expires_at: None,
+            deposit: joint_claim.deposit,
+            attesters: joint_claim.cosigned,
+        };
+        pallet.claims.insert(claim.clone(), record);
+        pallet.add_owned(&owner, &claim);
+        pallet.deposit_event(Event::ClaimCreated { owner, claim });
+        Ok(())
+    }
+
+    /// Withdraw a joint claim of the caller which is still waiting for signers, and return its
+    /// deposit.
+    pub fn cancel_joint_claim(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        claim: T::Content,
+    ) -> DispatchResult {
+        let pallet: &mut Self = runtime.as_mut();
+        let joint_claim = pallet
+            .joint_claims
+            .get(&claim)
+            .ok_or("Joint claim does not exist")?;
+        if joint_claim.owner != caller {
             return Err("Caller is not the owner of the claim");
         }
 
-        self.claims.remove(&claim);
+        let deposit = joint_claim.deposit;
+        pallet.joint_claims.remove(&claim);
+        pallet.deposit_event(Event::JointClaimCancelled {
+            owner: caller.clone(),
+            claim,
+        });
+        let currency: &mut T::Currency = runtime.as_mut();
+        currency.unreserve(&caller, deposit);
+        Ok(())
+    }
+
+    /// Claim `count` documents at once with the Merkle root of their hashes, built with
+    /// `merkle_root`. Each document is then proven with `verify_batch_claim` and its Merkle path.
+    pub fn create_batch_claim(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        root: T::Content,
+        count: u32,
+    ) -> DispatchResult {
+        if count == 0 {
+            return Err("Batch is empty");
+        }
+        let pallet: &mut Self = runtime.as_mut();
+        if pallet.batches.contains_key(&root) {
+            return Err("Batch already exists");
+        }
+        pallet.ensure_can_own(&caller)?;
+        let currency: &mut T::Currency = runtime.as_mut();
+        currency.reserve(&caller, T::CLAIM_DEPOSIT)?;
+
+        let system: &mut system::Pallet<T> = runtime.as_mut();
+        let record = BatchRecord {
+            owner: caller.clone(),
+            block_number: system.block_number(),
+            count,
+            deposit: T::CLAIM_DEPOSIT,
+        };
+        let pallet: &mut Self = runtime.as_mut();
+        pallet.batches.insert(root.clone(), record);
+        pallet.add_owned(&caller, &root);
+        pallet.deposit_event(Event::BatchCreated {
+            owner: caller,
+            root,
+            count,
+        });
+        Ok(())
+    }
+
+    /// Remove a batch of the caller, and return its deposit.
+    pub fn revoke_batch_claim(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        root: T::Content,
+    ) -> DispatchResult {
+        let pallet: &mut Self = runtime.as_mut();
+        let record = pallet.batches.get(&root).ok_or("Batch does not exist")?;
+        if record.owner != caller {
+            return Err("Caller is not the owner of the batch");
+        }
+
+        let deposit = record.deposit;
+        pallet.batches.remove(&root);
+        pallet.remove_owned(&caller, &root);
+        pallet.deposit_event(Event::BatchRevoked {
+            owner: caller.clone(),
+            root,
+        });
+        let currency: &mut T::Currency = runtime.as_mut();
+        currency.unreserve(&caller, deposit);
         Ok(())
     }
 }
+
 impl<T: Config> Pallet<T> {
     pub fn new() -> Self {
         Self {
             claims: BTreeMap::new(),
+            owned: BTreeMap::new(),
+            joint_claims: BTreeMap::new(),
+            batches: BTreeMap::new(),
+            expiries: BTreeMap::new(),
+            events: Vec::new(),
         }
     }
 
-    pub fn get_claim(&self, claim: &T::Content) -> Option<&T::AccountId> {
+    /// The record of a claim. Claims are pruned by `on_finalize` before the block they expire at,
+    /// so an expired claim is never returned.
+    pub fn get_claim(&self, claim: &T::Content) -> Option<&ClaimRecordOf<T>> {
         self.claims.get(claim)
     }
+
+    /// The hash under which a document is claimed.
+    pub fn hash_bytes(data: &[u8]) -> T::Content {
+        T::hash(data)
+    }
+
+    /// The hash under which the contents of a file are claimed.
+    pub fn hash_file(path: &Path) -> io::Result<T::Content> {
+        Ok(Self::hash_bytes(&fs::read(path)?))
+    }
+
+    /// Up to `limit` claims of `who`, in order, starting after the claim `start_after`. The last
+    /// claim of a page is the `start_after` of the next one. Batches are listed by their root.
+    pub fn claims_of(
+        &self,
+        who: &T::AccountId,
+        start_after: Option<&T::Content>,
+        limit: usize,
+    ) -> Vec<&T::Content> {
+        let Some(owned) = self.owned.get(who) else {
+            return Vec::new();
+        };
+        let start = match start_after {
+            Some(claim) => Bound::Excluded(claim),
+            None => Bound::Unbounded,
+        };
+        owned.range((start, Bound::Unbounded)).take(limit).collect()
+    }
+
+    /// A joint claim which is still waiting for signers.
+    pub fn get_joint_claim(&self, claim: &T::Content) -> Option<&JointClaimOf<T>> {
+        self.joint_claims.get(claim)
+    }
+
+    pub fn get_batch(&self, root: &T::Content) -> Option<&BatchRecordOf<T>> {
+        self.batches.get(root)
+    }
+
+    /// The batch `document` was claimed in, if `path` leads from it to the claimed `root`.
+    pub fn verify_batch_claim(
+        &self,
+        document: &T::Content,
+        path: &[T::Content],
+        root: &T::Content,
+    ) -> Option<&BatchRecordOf<T>> {
+        let batch = self.batches.get(root)?;
+        // Every path of a tree of `count` leaves has this length, so an inner node can't be passed
+        // off as a document with a shorter path.
+        let depth = u32::BITS - (batch.count - 1).leading_zeros();
+        if path.len() != depth as usize {
+            return None;
+        }
+        let node = path
+            .iter()
+            .fold(Self::hash_leaf(document), |node, sibling| {
+                Self::hash_node(&node, sibling)
+            });
+        (node == *root).then_some(batch)
+    }
+
+    /// The Merkle root of the hashes of a batch of documents, or `None` for an empty batch.
+    /// Pairs are sorted before being hashed, so paths don't need to say which side a sibling is
+    /// on, and the last node of a level with an odd length is paired with itself, so that every
+    /// path has the same length.
+    pub fn merkle_root(leaves: &[T::Content]) -> Option<T::Content> {
+        let mut level = leaves.iter().map(Self::hash_leaf).collect::<Vec<_>>();
+        while level.len() > 1 {
+            level = Self::merkle_level(&level);
+        }
+        level.pop()
+    }
+
+    /// The siblings on the way from the leaf at `index` to the Merkle root, leaf first.
+    pub fn merkle_path(leaves: &[T::Content], mut index: usize) -> Option<Vec<T::Content>> {
+        if index >= leaves.len() {
+            return None;
+        }
+        let mut level = leaves.iter().map(Self::hash_leaf).collect::<Vec<_>>();
+        let mut path = Vec::new();
+        while level.len() > 1 {
+            let sibling = level.get(index ^ 1).unwrap_or(&level[index]);
+            path.push(sibling.clone());
+            level = Self::merkle_level(&level);
+            index /= 2;
+        }
+        Some(path)
+    }
+
+    fn merkle_level(level: &[T::Content]) -> Vec<T::Content> {
+        level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => Self::hash_node(left, right),
+                _ => Self::hash_node(&pair[0], &pair[0]),
+            })
+            .collect()
+    }
+
+    /// Leaves and inner nodes are hashed with different prefixes, so that one can't be mistaken
+    /// for the other.
+    fn hash_leaf(document: &T::Content) -> T::Content {
+        T::hash(&[&[0], document.as_ref()].concat())
+    }
+
+    fn hash_node(a: &T::Content, b: &T::Content) -> T::Content {
+        let (first, second) = if a <= b { (a, b) } else { (b, a) };
+        T::hash(&[&[1], first.as_ref(), second.as_ref()].concat())
+    }
+
+    /// The deposit held for a claim with `description`.
+    pub fn deposit_for(description: Option<&str>) -> Result<T::Balance, DispatchError> {
+        let bytes = T::Balance::from(description.map_or(0, str::len) as u32);
+        T::DEPOSIT_PER_BYTE
+            .checked_mul(&bytes)
+            .and_then(|deposit| deposit.checked_add(&T::CLAIM_DEPOSIT))
+            .ok_or("Deposit overflow")
+    }
+
+    /// Check that `claim` is neither claimed nor proposed as a joint claim.
+    fn ensure_unclaimed(&self, claim: &T::Content) -> DispatchResult {
+        if self.claims.contains_key(claim) || self.joint_claims.contains_key(claim) {
+            return Err("Claim already exists");
+        }
+        Ok(())
+    }
+
+    fn ensure_can_own(&self, who: &T::AccountId) -> DispatchResult {
+        let owned = self.owned.get(who).map_or(0, BTreeSet::len);
+        if owned >= T::MAX_CLAIMS_PER_ACCOUNT {
+            return Err("Too many claims");
+        }
+        Ok(())
+    }
+
+    fn add_owned(&mut self, who: &T::AccountId, claim: &T::Content) {
+        self.owned
+            .entry(who.clone())
+            .or_default()
+            .insert(claim.clone());
+    }
+
+    fn remove_owned(&mut self, who: &T::AccountId, claim: &T::Content) {
+        if let Some(owned) = self.owned.get_mut(who) {
+            owned.remove(claim);
+            if owned.is_empty() {
+                self.owned.remove(who);
+            }
+        }
+    }
+
+    /// Remove a claim from the expiry index, at the block it was set to expire at.
+    fn remove_expiry(&mut self, claim: &T::Content, expires_at: Option<T::BlockNumber>) {
+        let Some(expires_at) = expires_at else {
+            return;
+        };
+        if let Some(claims) = self.expiries.get_mut(&expires_at) {
+            claims.retain(|c| c != claim);
+            if claims.is_empty() {
+                self.expiries.remove(&expires_at);
+            }
+        }
+    }
+
+    fn deposit_event(&mut self, event: Event<T>) {
+        self.events.push(event);
+    }
+
+    pub fn take_events(&mut self) -> Vec<Event<T>> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl<T: Config> StorageSize for Pallet<T> {
+    fn storage_size(&self) -> usize {
+        self.claims.len()
+            + self.joint_claims.len()
+            + self.batches.len()
+            + self.expiries.values().map(Vec::len).sum::<usize>()
+    }
+}
+
+impl<T: Config> Hooks<T::Runtime> for Pallet<T> {
+    /// Prune the claims which are no longer valid in the next block, and return their deposits.
+    /// At the last block number there is no next block, and only the claims which are already
+    /// expired are pruned.
+    fn on_finalize(runtime: &mut T::Runtime) {
+        let system: &mut system::Pallet<T> = runtime.as_mut();
+        let now = system.block_number();
+        let next = now.checked_add(&T::BlockNumber::one()).unwrap_or(now);
+
+        let pallet: &mut Self = runtime.as_mut();
+        let mut expired = Vec::new();
+        while let Some(entry) = pallet.expiries.first_entry() {
+            if *entry.key() > next {
+                break;
+            }
+            for claim in entry.remove() {
+                if let Some(record) = pallet.claims.remove(&claim) {
+                    pallet.remove_owned(&record.owner, &claim);
+                    expired.push((record.owner.clone(), record.deposit));
+                    pallet.deposit_event(Event::ClaimExpired {
+                        owner: record.owner,
+                        claim,
+                    });
+                }
+            }
+        }
+
+        let currency: &mut T::Currency = runtime.as_mut();
+        for (owner, deposit) in expired {
+            currency.unreserve(&owner, deposit);
+        }
+    }
+}
+
+impl<AccountId: ToJson, BlockNumber: ToJson, Balance: ToJson> ToJson
+    for ClaimRecord<AccountId, BlockNumber, Balance>
+{
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("owner", self.owner.to_json()),
+            ("block_number", self.block_number.to_json()),
+            ("timestamp", self.timestamp.to_json()),
+            ("description", self.description.to_json()),
+            ("previous_owners", self.previous_owners.to_json()),
+            ("pending_owner", self.pending_owner.to_json()),
+            ("expires_at", self.expires_at.to_json()),
+            ("deposit", self.deposit.to_json()),
+            ("attesters", self.attesters.to_json()),
+        ])
+    }
+}
+
+impl<AccountId: ToJson, BlockNumber: ToJson, Balance: ToJson> ToJson
+    for BatchRecord<AccountId, BlockNumber, Balance>
+{
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("owner", self.owner.to_json()),
+            ("block_number", self.block_number.to_json()),
+            ("count", self.count.to_json()),
+            ("deposit", self.deposit.to_json()),
+        ])
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::ClaimRecord;
+    use crate::hashing::{H256, sha256};
+    use crate::support::{Currency, Hooks, ReservableCurrency};
+    use crate::{balances, system};
+
     struct TestConfig;
     impl super::Config for TestConfig {
-        type Content = &'static str;
+        type Content = H256;
+
+        fn hash(data: &[u8]) -> H256 {
+            sha256(data)
+        }
+
+        type Balance = u128;
+        type Currency = balances::Pallet<TestConfig>;
+        const CLAIM_DEPOSIT: u128 = 10;
+        const DEPOSIT_PER_BYTE: u128 = 1;
+        const MAX_CLAIMS_PER_ACCOUNT: usize = 3;
+        type Runtime = TestRuntime;
+    }
+
+    impl balances::Config for TestConfig {
+        type Balance = u128;
+        const EXISTENTIAL_DEPOSIT: u128 = 1;
     }
 
-    impl crate::system::Config for TestConfig {
+    impl system::Config for TestConfig {
         type AccountId = String;
         type BlockNumber = u32;
         type Nonce = u32;
+        type RuntimeEvent = ();
+    }
+
+    struct TestRuntime {
+        system: system::Pallet<TestConfig>,
+        balances: balances::Pallet<TestConfig>,
+        poe: super::Pallet<TestConfig>,
+    }
+
+    impl AsMut<system::Pallet<TestConfig>> for TestRuntime {
+        fn as_mut(&mut self) -> &mut system::Pallet<TestConfig> {
+            &mut self.system
+        }
+    }
+
+    impl AsMut<balances::Pallet<TestConfig>> for TestRuntime {
+        fn as_mut(&mut self) -> &mut balances::Pallet<TestConfig> {
+            &mut self.balances
+        }
+    }
+
+    impl AsMut<super::Pallet<TestConfig>> for TestRuntime {
+        fn as_mut(&mut self) -> &mut super::Pallet<TestConfig> {
+            &mut self.poe
+        }
+    }
+
+    type PoE = super::Pallet<TestConfig>;
+
+    fn new_runtime() -> TestRuntime {
+        let mut runtime = TestRuntime {
+            system: system::Pallet::new(),
+            balances: balances::Pallet::new(),
+            poe: PoE::new(),
+        };
+        for who in ["Alice", "Bob", "Charlie"] {
+            runtime.balances.set_balance(&who.to_string(), 100);
+        }
+        runtime
     }
 
     #[test]
     fn basic_proof_of_existence() {
-        let mut poe = super::Pallet::<TestConfig>::new();
+        let mut runtime = new_runtime();
+        let document = PoE::hash_bytes(b"my_document");
+        let non_existent = PoE::hash_bytes(b"non_existent");
 
         // Success: create a claim
-        poe.create_claim("Alice".to_string(), "my_document")
-            .unwrap();
-        assert_eq!(poe.get_claim(&"my_document"), Some(&"Alice".to_string()));
+        PoE::create_claim(
+            &mut runtime,
+            "Alice".to_string(),
+            document,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            runtime.poe.get_claim(&document).map(|r| &r.owner),
+            Some(&"Alice".to_string())
+        );
 
         // Error: revoke not owned claim
-        let res = poe.revoke_claim("Bob".to_string(), "my_document");
+        let res = PoE::revoke_claim(&mut runtime, "Bob".to_string(), document);
         assert_eq!(res, Err("Caller is not the owner of the claim"));
 
         // Error: create existing claim
-        let res = poe.create_claim("Bob".to_string(), "my_document");
+        let res = PoE::create_claim(&mut runtime, "Bob".to_string(), document, None, None, None);
         assert_eq!(res, Err("Claim already exists"));
 
         // Error: revoke non-existent claim
-        let res = poe.revoke_claim("Alice".to_string(), "non_existent");
+        let res = PoE::revoke_claim(&mut runtime, "Alice".to_string(), non_existent);
         assert_eq!(res, Err("Claim does not exist"));
 
         // Success: revoke owned claim
-        let res = poe.revoke_claim("Alice".to_string(), "my_document");
+        let res = PoE::revoke_claim(&mut runtime, "Alice".to_string(), document);
         assert_eq!(res, Ok(()));
-        assert_eq!(poe.get_claim(&"my_document"), None);
+        assert_eq!(runtime.poe.get_claim(&document), None);
+    }
+
+    #[test]
+    fn claim_records() {
+        let mut runtime = new_runtime();
+        let document = PoE::hash_bytes(b"contract.pdf");
+        runtime.system.inc_block_number();
+        runtime.system.inc_block_number();
+
+        let description = Some("https://example.com/contract.pdf".to_string());
+        let timestamp = Some(1_700_000_000_000);
+        PoE::create_claim(
+            &mut runtime,
+            "Alice".to_string(),
+            document,
+            description.clone(),
+            timestamp,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            runtime.poe.get_claim(&document),
+            Some(&ClaimRecord {
+                owner: "Alice".to_string(),
+                block_number: 2,
+                timestamp,
+                description,
+                previous_owners: vec![],
+                pending_owner: None,
+                expires_at: None,
+                deposit: 10 + 32,
+                attesters: vec![],
+            })
+        );
+
+        let other = PoE::hash_bytes(b"other");
+        let description = Some("x".repeat(super::DESCRIPTION_LIMIT + 1));
+        let res = PoE::create_claim(
+            &mut runtime,
+            "Alice".to_string(),
+            other,
+            description,
+            None,
+            None,
+        );
+        assert_eq!(res, Err("Description too long"));
+    }
+
+    #[test]
+    fn transfer_claims() {
+        let mut runtime = new_runtime();
+        let document = PoE::hash_bytes(b"my_document");
+        let (alice, bob, charlie) = (
+            "Alice".to_string(),
+            "Bob".to_string(),
+            "Charlie".to_string(),
+        );
+        runtime.system.inc_block_number();
+        PoE::create_claim(&mut runtime, alice.clone(), document, None, None, None).unwrap();
+        runtime.system.inc_block_number();
+
+        let res = runtime
+            .poe
+            .transfer_claim(bob.clone(), document, charlie.clone());
+        assert_eq!(res, Err("Caller is not the owner of the claim"));
+        runtime
+            .poe
+            .transfer_claim(alice.clone(), document, bob.clone())
+            .unwrap();
+
+        // The claim only changes hands once it is accepted.
+        let res = PoE::accept_claim(&mut runtime, charlie.clone(), document);
+        assert_eq!(res, Err("Claim was not offered to the caller"));
+        assert_eq!(runtime.poe.get_claim(&document).unwrap().owner, alice);
+        PoE::accept_claim(&mut runtime, bob.clone(), document).unwrap();
+
+        runtime
+            .poe
+            .transfer_claim(bob.clone(), document, charlie.clone())
+            .unwrap();
+        PoE::accept_claim(&mut runtime, charlie.clone(), document).unwrap();
+        let record = runtime.poe.get_claim(&document).unwrap();
+        assert_eq!(record.owner, charlie);
+        assert_eq!(record.previous_owners, vec![alice, bob.clone()]);
+        assert_eq!(record.block_number, 1);
+        assert_eq!(record.pending_owner, None);
+
+        let res = PoE::accept_claim(&mut runtime, bob, document);
+        assert_eq!(res, Err("Claim was not offered to the caller"));
+    }
+
+    #[test]
+    fn expire_and_renew_claims() {
+        let mut runtime = new_runtime();
+        let alice = "Alice".to_string();
+        let (first, second) = (PoE::hash_bytes(b"first"), PoE::hash_bytes(b"second"));
+        runtime.system.inc_block_number();
+
+        let res = PoE::create_claim(&mut runtime, alice.clone(), first, None, None, Some(1));
+        assert_eq!(res, Err("Expiry must be in the future"));
+        PoE::create_claim(&mut runtime, alice.clone(), first, None, None, Some(3)).unwrap();
+        PoE::create_claim(&mut runtime, alice.clone(), second, None, None, Some(3)).unwrap();
+        PoE::renew_claim(&mut runtime, alice.clone(), second, Some(5)).unwrap();
+
+        // At the end of block 2, the claims which expire at block 3 are pruned.
+        runtime.system.inc_block_number();
+        PoE::on_finalize(&mut runtime);
+        assert_eq!(runtime.poe.get_claim(&first), None);
+        assert!(runtime.poe.get_claim(&second).is_some());
+        let res = PoE::renew_claim(&mut runtime, alice.clone(), first, Some(10));
+        assert_eq!(res, Err("Claim does not exist"));
+
+        // A claim without an expiry is never pruned.
+        PoE::renew_claim(&mut runtime, alice.clone(), second, None).unwrap();
+        for _ in 0..5 {
+            runtime.system.inc_block_number();
+            PoE::on_finalize(&mut runtime);
+        }
+        assert!(runtime.poe.get_claim(&second).is_some());
+        assert!(runtime.poe.expiries.is_empty());
+    }
+
+    #[test]
+    fn claim_deposits() {
+        let mut runtime = new_runtime();
+        let (alice, bob) = ("Alice".to_string(), "Bob".to_string());
+        let document = PoE::hash_bytes(b"my_document");
+
+        let description = Some("a contract".to_string());
+        PoE::create_claim(
+            &mut runtime,
+            alice.clone(),
+            document,
+            description,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(runtime.balances.reserved_balance(&alice), 20);
+        assert_eq!(runtime.balances.balance_of(&alice), 80);
+
+        // The deposit follows the claim to its new owner.
+        runtime
+            .poe
+            .transfer_claim(alice.clone(), document, bob.clone())
+            .unwrap();
+        PoE::accept_claim(&mut runtime, bob.clone(), document).unwrap();
+        assert_eq!(runtime.balances.reserved_balance(&alice), 0);
+        assert_eq!(runtime.balances.reserved_balance(&bob), 20);
+
+        PoE::revoke_claim(&mut runtime, bob.clone(), document).unwrap();
+        assert_eq!(runtime.balances.reserved_balance(&bob), 0);
+        assert_eq!(runtime.balances.balance_of(&bob), 100);
+
+        // Expired claims return their deposit too.
+        runtime.system.inc_block_number();
+        PoE::create_claim(&mut runtime, alice.clone(), document, None, None, Some(2)).unwrap();
+        assert_eq!(runtime.balances.reserved_balance(&alice), 10);
+        PoE::on_finalize(&mut runtime);
+        assert_eq!(runtime.balances.reserved_balance(&alice), 0);
+
+        let description = Some("x".repeat(100));
+        let res = PoE::create_claim(
+            &mut runtime,
+            alice.clone(),
+            document,
+            description,
+            None,
+            None,
+        );
+        assert_eq!(res, Err("Insufficient balance"));
+        assert_eq!(runtime.poe.get_claim(&document), None);
+    }
+
+    #[test]
+    fn batch_claims() {
+        let mut runtime = new_runtime();
+        let alice = "Alice".to_string();
+        let documents: Vec<_> = (0..5)
+            .map(|i| PoE::hash_bytes(format!("document {}", i).as_bytes()))
+            .collect();
+        let root = PoE::merkle_root(&documents).unwrap();
+        runtime.system.inc_block_number();
+
+        let res = PoE::create_batch_claim(&mut runtime, alice.clone(), root, 0);
+        assert_eq!(res, Err("Batch is empty"));
+        PoE::create_batch_claim(&mut runtime, alice.clone(), root, 5).unwrap();
+        assert_eq!(runtime.balances.reserved_balance(&alice), 10);
+        let res = PoE::create_batch_claim(&mut runtime, alice.clone(), root, 5);
+        assert_eq!(res, Err("Batch already exists"));
+
+        for (index, document) in documents.iter().enumerate() {
+            let path = PoE::merkle_path(&documents, index).unwrap();
+            let batch = runtime.poe.verify_batch_claim(document, &path, &root);
+            assert_eq!(
+                batch.map(|batch| (&batch.owner, batch.block_number)),
+                Some((&alice, 1))
+            );
+        }
+
+        // A document outside the batch, or a path for another document, is not verified.
+        let path = PoE::merkle_path(&documents, 0).unwrap();
+        let other = PoE::hash_bytes(b"other");
+        assert_eq!(runtime.poe.verify_batch_claim(&other, &path, &root), None);
+        assert_eq!(
+            runtime.poe.verify_batch_claim(&documents[1], &path, &root),
+            None
+        );
+        assert_eq!(PoE::merkle_path(&documents, 5), None);
+
+        // Neither the root nor an inner node verifies as a document.
+        assert_eq!(runtime.poe.verify_batch_claim(&root, &[], &root), None);
+        let inner = PoE::hash_node(&PoE::hash_leaf(&documents[0]), &path[0]);
+        assert_eq!(
+            runtime.poe.verify_batch_claim(&inner, &path[1..], &root),
+            None
+        );
+        let mut long_path = path.clone();
+        long_path.push(root);
+        assert_eq!(
+            runtime
+                .poe
+                .verify_batch_claim(&documents[0], &long_path, &root),
+            None
+        );
+
+        let res = PoE::revoke_batch_claim(&mut runtime, "Bob".to_string(), root);
+        assert_eq!(res, Err("Caller is not the owner of the batch"));
+        PoE::revoke_batch_claim(&mut runtime, alice.clone(), root).unwrap();
+        assert_eq!(runtime.balances.reserved_balance(&alice), 0);
+        assert_eq!(
+            runtime.poe.verify_batch_claim(&documents[0], &path, &root),
+            None
+        );
+    }
+
+    #[test]
+    fn claims_of_owner() {
+        let mut runtime = new_runtime();
+        let (alice, bob) = ("Alice".to_string(), "Bob".to_string());
+        let mut documents: Vec<_> = (0..4)
+            .map(|i| PoE::hash_bytes(format!("document {}", i).as_bytes()))
+            .collect();
+        runtime.system.inc_block_number();
+        for &document in &documents[..3] {
+            PoE::create_claim(&mut runtime, alice.clone(), document, None, None, Some(2)).unwrap();
+        }
+        let res = PoE::create_claim(&mut runtime, alice.clone(), documents[3], None, None, None);
+        assert_eq!(res, Err("Too many claims"));
+
+        documents.truncate(3);
+        documents.sort();
+        let page = runtime.poe.claims_of(&alice, None, 2);
+        assert_eq!(page, vec![&documents[0], &documents[1]]);
+        let page = runtime.poe.claims_of(&alice, page.last().copied(), 2);
+        assert_eq!(page, vec![&documents[2]]);
+
+        // The index follows transfers, revocations and expiries.
+        runtime
+            .poe
+            .transfer_claim(alice.clone(), documents[0], bob.clone())
+            .unwrap();
+        PoE::accept_claim(&mut runtime, bob.clone(), documents[0]).unwrap();
+        PoE::revoke_claim(&mut runtime, alice.clone(), documents[1]).unwrap();
+        assert_eq!(runtime.poe.claims_of(&alice, None, 10), vec![&documents[2]]);
+        assert_eq!(runtime.poe.claims_of(&bob, None, 10), vec![&documents[0]]);
+
+        PoE::on_finalize(&mut runtime);
+        assert!(runtime.poe.claims_of(&alice, None, 10).is_empty());
+        assert!(runtime.poe.claims_of(&bob, None, 10).is_empty());
+        assert!(runtime.poe.owned.is_empty());
+
+        // A batch is listed by its root, and counts as a single claim towards the limit.
+        let root = PoE::merkle_root(&documents).unwrap();
+        PoE::create_batch_claim(&mut runtime, alice.clone(), root, 3).unwrap();
+        assert_eq!(runtime.poe.claims_of(&alice, None, 10), vec![&root]);
+        for &document in &documents[..2] {
+            PoE::create_claim(&mut runtime, alice.clone(), document, None, None, None).unwrap();
+        }
+        let res = PoE::create_claim(&mut runtime, alice.clone(), documents[2], None, None, None);
+        assert_eq!(res, Err("Too many claims"));
+        PoE::revoke_claim(&mut runtime, alice.clone(), documents[0]).unwrap();
+        let other = PoE::merkle_root(&documents[..2]).unwrap();
+        PoE::create_batch_claim(&mut runtime, alice.clone(), other, 2).unwrap();
+        let res = PoE::create_batch_claim(&mut runtime, alice.clone(), documents[2], 1);
+        assert_eq!(res, Err("Too many claims"));
+
+        PoE::revoke_batch_claim(&mut runtime, alice.clone(), root).unwrap();
+        PoE::revoke_batch_claim(&mut runtime, alice.clone(), other).unwrap();
+        assert_eq!(runtime.poe.claims_of(&alice, None, 10), vec![&documents[1]]);
+    }
+
+    #[test]
+    fn joint_claims() {
+        let mut runtime = new_runtime();
+        let (alice, bob, charlie) = (
+            "Alice".to_string(),
+            "Bob".to_string(),
+            "Charlie".to_string(),
+        );
+        let contract = PoE::hash_bytes(b"contract");
+        let signers = vec![charlie.clone(), bob.clone()];
+
+        let res = PoE::propose_joint_claim(&mut runtime, alice.clone(), contract, vec![]);
+        assert_eq!(res, Err("Joint claim needs signers"));
+        let res = PoE::propose_joint_claim(
+            &mut runtime,
+            alice.clone(),
+            contract,
+            vec![bob.clone(), bob.clone()],
+        );
+        assert_eq!(res, Err("Signers must be unique"));
+        PoE::propose_joint_claim(&mut runtime, alice.clone(), contract, signers.clone()).unwrap();
+        assert_eq!(runtime.balances.reserved_balance(&alice), 10);
+        let res = PoE::create_claim(&mut runtime, bob.clone(), contract, None, None, None);
+        assert_eq!(res, Err("Claim already exists"));
+
+        // The claim stays pending until every signer cosigned it.
+        PoE::cosign_claim(&mut runtime, charlie.clone(), contract).unwrap();
+        let res = PoE::cosign_claim(&mut runtime, charlie.clone(), contract);
+        assert_eq!(res, Err("Already cosigned"));
+        let res = PoE::cosign_claim(&mut runtime, alice.clone(), contract);
+        assert_eq!(res, Err("Caller is not a required signer"));
+        assert_eq!(runtime.poe.get_claim(&contract), None);
+
+        runtime.system.inc_block_number();
+        PoE::cosign_claim(&mut runtime, bob.clone(), contract).unwrap();
+        assert_eq!(runtime.poe.get_joint_claim(&contract), None);
+        let record = runtime.poe.get_claim(&contract).unwrap();
+        assert_eq!(record.owner, alice);
+        assert_eq!(record.block_number, 1);
+        assert_eq!(record.attesters, vec![bob.clone(), charlie]);
+        assert_eq!(runtime.poe.claims_of(&alice, None, 10), vec![&contract]);
+
+        // A pending joint claim can be withdrawn by its owner.
+        let other = PoE::hash_bytes(b"other");
+        PoE::propose_joint_claim(&mut runtime, alice.clone(), other, vec![bob.clone()]).unwrap();
+        let res = PoE::cancel_joint_claim(&mut runtime, bob.clone(), other);
+        assert_eq!(res, Err("Caller is not the owner of the claim"));
+        PoE::cancel_joint_claim(&mut runtime, alice.clone(), other).unwrap();
+        assert_eq!(runtime.balances.reserved_balance(&alice), 10);
+        let res = PoE::cosign_claim(&mut runtime, bob, other);
+        assert_eq!(res, Err("Joint claim does not exist"));
     }
 }
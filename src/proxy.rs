@@ -0,0 +1,537 @@
+use num::{CheckedAdd, Zero};
+use std::collections::BTreeMap;
+
+use crate::hashing::{H256, sha256};
+use crate::json::{FromJson, ToJson, Value};
+use crate::support::{DEFAULT_WEIGHT, Dispatch, DispatchResult, GetWeight, Hooks, StorageSize};
+use crate::system;
+
+/// The most proxies an account can have, and the most announcements a proxy can have pending.
+const MAX_PROXIES: usize = 32;
+
+/// Decides which calls a proxy of some type can make. The runtime implements it, since only the
+/// runtime knows about all of its calls.
+pub trait InstanceFilter<Call> {
+    fn filter(&self, call: &Call) -> bool;
+}
+
+pub trait Config: crate::system::Config<BlockNumber: Ord + CheckedAdd> {
+    type RuntimeCall: GetWeight + ToJson;
+    type ProxyType: InstanceFilter<Self::RuntimeCall> + std::fmt::Debug + Copy + PartialEq + ToJson;
+    /// Calls are dispatched through the runtime as the real account, and announcements are timed
+    /// with the block number of the system pallet.
+    type Runtime: AsMut<Pallet<Self>>
+        + AsMut<system::Pallet<Self>>
+        + Dispatch<Caller = Self::AccountId, Call = Self::RuntimeCall>;
+}
+
+/// What a proxy is allowed to do on behalf of its real account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyType {
+    Any,
+    /// Anything except moving funds, or managing proxies.
+    NonTransfer,
+    /// Only proof of existence calls.
+    ClaimsOnly,
+}
+
+/// A proxy of an account. With a `delay`, the proxy must announce its calls and wait that many
+/// blocks before making them, which gives the real account a chance to reject them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProxyDefinition<AccountId, ProxyType, BlockNumber> {
+    pub delegate: AccountId,
+    pub proxy_type: ProxyType,
+    pub delay: BlockNumber,
+}
+
+/// A call a proxy announced it will make for `real`, at block `height`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Announcement<AccountId, BlockNumber> {
+    pub real: AccountId,
+    pub call_hash: H256,
+    pub height: BlockNumber,
+}
+
+pub type ProxyDefinitionOf<T> = ProxyDefinition<
+    <T as system::Config>::AccountId,
+    <T as Config>::ProxyType,
+    <T as system::Config>::BlockNumber,
+>;
+pub type AnnouncementOf<T> =
+    Announcement<<T as system::Config>::AccountId, <T as system::Config>::BlockNumber>;
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+    /// The proxies of each real account.
+    proxies: BTreeMap<T::AccountId, Vec<ProxyDefinitionOf<T>>>,
+    /// The pending announcements of each proxy.
+    announcements: BTreeMap<T::AccountId, Vec<AnnouncementOf<T>>>,
+    events: Vec<Event<T>>,
+}
+
+#[macros::event]
+pub enum Event<T: Config> {
+    ProxyAdded {
+        delegator: T::AccountId,
+        delegate: T::AccountId,
+        proxy_type: T::ProxyType,
+        delay: T::BlockNumber,
+    },
+    ProxyRemoved {
+        delegator: T::AccountId,
+        delegate: T::AccountId,
+        proxy_type: T::ProxyType,
+    },
+    Announced {
+        real: T::AccountId,
+        proxy: T::AccountId,
+        call_hash: H256,
+    },
+    AnnouncementRejected {
+        real: T::AccountId,
+        proxy: T::AccountId,
+        call_hash: H256,
+    },
+    ProxyExecuted {
+        real: T::AccountId,
+        proxy: T::AccountId,
+    },
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Let `delegate` make the calls allowed by `proxy_type` on behalf of the caller.
+    pub fn add_proxy(
+        &mut self,
+        caller: T::AccountId,
+        delegate: T::AccountId,
+        proxy_type: T::ProxyType,
+        delay: T::BlockNumber,
+    ) -> DispatchResult {
+        if delegate == caller {
+            return Err("Cannot add self as proxy");
+        }
+        let proxies = self.proxies.entry(caller.clone()).or_default();
+        if proxies
+            .iter()
+            .any(|p| p.delegate == delegate && p.proxy_type == proxy_type)
+        {
+            return Err("Proxy already exists");
+        }
+        if proxies.len() >= MAX_PROXIES {
+            return Err("Too many proxies");
+        }
+
+        proxies.push(ProxyDefinition {
+            delegate: delegate.clone(),
+            proxy_type,
+            delay,
+        });
+        self.deposit_event(Event::ProxyAdded {
+            delegator: caller,
+            delegate,
+            proxy_type,
+            delay,
+        });
+        Ok(())
+    }
+
+    pub fn remove_proxy(
+        &mut self,
+        caller: T::AccountId,
+        delegate: T::AccountId,
+        proxy_type: T::ProxyType,
+    ) -> DispatchResult {
+        let proxies = self.proxies.get_mut(&caller).ok_or("Proxy not found")?;
+        let index = proxies
+            .iter()
+            .position(|p| p.delegate == delegate && p.proxy_type == proxy_type)
+            .ok_or("Proxy not found")?;
+
+        proxies.remove(index);
+        if proxies.is_empty() {
+            self.proxies.remove(&caller);
+        }
+        self.deposit_event(Event::ProxyRemoved {
+            delegator: caller,
+            delegate,
+            proxy_type,
+        });
+        Ok(())
+    }
+
+    /// Dispatch `call` as `real`, where the caller is a proxy of `real` without a delay.
+    #[weight(call.weight() + DEFAULT_WEIGHT)]
+    pub fn proxy(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        real: T::AccountId,
+        call: Box<T::RuntimeCall>,
+    ) -> DispatchResult {
+        let pallet: &mut Self = runtime.as_mut();
+        let proxies = pallet
+            .proxies_of(&real)
+            .iter()
+            .filter(|p| p.delegate == caller)
+            .collect::<Vec<_>>();
+        if proxies.is_empty() {
+            return Err("Not a proxy");
+        }
+        if !proxies.iter().any(|p| p.delay.is_zero()) {
+            return Err("Proxy has a delay, announce the call first");
+        }
+        if !proxies
+            .iter()
+            .any(|p| p.delay.is_zero() && p.proxy_type.filter(&call))
+        {
+            return Err("Call filtered by the proxy type");
+        }
+
+        Self::execute(runtime, caller, real, *call)
+    }
+
+    /// Announce a call the caller will make as `real` once the delay of its proxy has passed.
+    pub fn announce(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        real: T::AccountId,
+        call_hash: H256,
+    ) -> DispatchResult {
+        let system: &mut system::Pallet<T> = runtime.as_mut();
+        let height = system.block_number();
+
+        let pallet: &mut Self = runtime.as_mut();
+        if !pallet
+            .proxies_of(&real)
+            .iter()
+            .any(|p| p.delegate == caller)
+        {
+            return Err("Not a proxy");
+        }
+        let announcements = pallet.announcements.entry(caller.clone()).or_default();
+        if announcements.len() >= MAX_PROXIES {
+            return Err("Too many announcements");
+        }
+
+        announcements.push(Announcement {
+            real: real.clone(),
+            call_hash,
+            height,
+        });
+        pallet.deposit_event(Event::Announced {
+            real,
+            proxy: caller,
+            call_hash,
+        });
+        Ok(())
+    }
+
+    /// Reject a call announced by the proxy `delegate` of the caller.
+    pub fn reject_announcement(
+        &mut self,
+        caller: T::AccountId,
+        delegate: T::AccountId,
+        call_hash: H256,
+    ) -> DispatchResult {
+        self.remove_announcement(&delegate, &caller, call_hash)?;
+        self.deposit_event(Event::AnnouncementRejected {
+            real: caller,
+            proxy: delegate,
+            call_hash,
+        });
+        Ok(())
+    }
+
+    /// Dispatch a call announced by the caller as `real`, once the delay of its proxy has passed.
+    #[weight(call.weight() + DEFAULT_WEIGHT)]
+    pub fn proxy_announced(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        real: T::AccountId,
+        call: Box<T::RuntimeCall>,
+    ) -> DispatchResult {
+        let system: &mut system::Pallet<T> = runtime.as_mut();
+        let now = system.block_number();
+
+        let pallet: &mut Self = runtime.as_mut();
+        let call_hash = Self::call_hash(&call);
+        let announcement = pallet
+            .announcements
+            .get(&caller)
+            .and_then(|a| {
+                a.iter()
+                    .find(|a| a.real == real && a.call_hash == call_hash)
+            })
+            .ok_or("Announcement not found")?;
+        // The announcement can be used by any proxy of `real` with a delay which has passed. A
+        // delay which overflows the block number is never due.
+        let passes = |proxy: &&ProxyDefinitionOf<T>| {
+            let due = announcement.height.checked_add(&proxy.delay);
+            proxy.delegate == caller && due.is_some_and(|due| due <= now)
+        };
+        let proxies = pallet
+            .proxies_of(&real)
+            .iter()
+            .filter(passes)
+            .collect::<Vec<_>>();
+        if proxies.is_empty() {
+            return Err("Announcement is not yet due");
+        }
+        if !proxies.iter().any(|p| p.proxy_type.filter(&call)) {
+            return Err("Call filtered by the proxy type");
+        }
+
+        pallet.remove_announcement(&caller, &real, call_hash)?;
+        Self::execute(runtime, caller, real, *call)
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Self {
+            proxies: BTreeMap::new(),
+            announcements: BTreeMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn proxies_of(&self, real: &T::AccountId) -> &[ProxyDefinitionOf<T>] {
+        self.proxies.get(real).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn announcements_of(&self, delegate: &T::AccountId) -> &[AnnouncementOf<T>] {
+        self.announcements.get(delegate).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn call_hash(call: &T::RuntimeCall) -> H256 {
+        sha256(call.to_json().to_string().as_bytes())
+    }
+
+    fn execute(
+        runtime: &mut T::Runtime,
+        proxy: T::AccountId,
+        real: T::AccountId,
+        call: T::RuntimeCall,
+    ) -> DispatchResult {
+        runtime.dispatch(real.clone(), call)?;
+        let pallet: &mut Self = runtime.as_mut();
+        pallet.deposit_event(Event::ProxyExecuted { real, proxy });
+        Ok(())
+    }
+
+    fn remove_announcement(
+        &mut self,
+        delegate: &T::AccountId,
+        real: &T::AccountId,
+        call_hash: H256,
+    ) -> DispatchResult {
+        let announcements = self
+            .announcements
+            .get_mut(delegate)
+            .ok_or("Announcement not found")?;
+        let index = announcements
+            .iter()
+            .position(|a| &a.real == real && a.call_hash == call_hash)
+            .ok_or("Announcement not found")?;
+
+        announcements.remove(index);
+        if announcements.is_empty() {
+            self.announcements.remove(delegate);
+        }
+        Ok(())
+    }
+
+    fn deposit_event(&mut self, event: Event<T>) {
+        self.events.push(event);
+    }
+
+    pub fn take_events(&mut self) -> Vec<Event<T>> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl<T: Config> StorageSize for Pallet<T> {
+    fn storage_size(&self) -> usize {
+        let proxies = self.proxies.values().map(Vec::len).sum::<usize>();
+        let announcements = self.announcements.values().map(Vec::len).sum::<usize>();
+        proxies + announcements
+    }
+}
+
+impl<T: Config, Runtime> Hooks<Runtime> for Pallet<T> {}
+
+/// A proxy type is encoded as the name of its variant, e.g. `"ClaimsOnly"`.
+impl ToJson for ProxyType {
+    fn to_json(&self) -> Value {
+        let name = match self {
+            ProxyType::Any => "Any",
+            ProxyType::NonTransfer => "NonTransfer",
+            ProxyType::ClaimsOnly => "ClaimsOnly",
+        };
+        name.to_json()
+    }
+}
+
+impl FromJson for ProxyType {
+    fn from_json(value: &Value) -> Result<Self, &'static str> {
+        match value.as_str() {
+            Some("Any") => Ok(ProxyType::Any),
+            Some("NonTransfer") => Ok(ProxyType::NonTransfer),
+            Some("ClaimsOnly") => Ok(ProxyType::ClaimsOnly),
+            _ => Err("Unknown proxy type"),
+        }
+    }
+}
+
+impl<AccountId: ToJson, ProxyType: ToJson, BlockNumber: ToJson> ToJson
+    for ProxyDefinition<AccountId, ProxyType, BlockNumber>
+{
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("delegate", self.delegate.to_json()),
+            ("proxy_type", self.proxy_type.to_json()),
+            ("delay", self.delay.to_json()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProxyType;
+    use crate::chain_spec::ChainSpec;
+    use crate::{Runtime, RuntimeCall, balances, proof_of_existence, utility};
+
+    type Proxy = super::Pallet<Runtime>;
+    type PoE = proof_of_existence::Pallet<Runtime>;
+
+    fn transfer(to: &str, amount: u128) -> RuntimeCall {
+        RuntimeCall::balances(balances::Call::transfer {
+            to: to.to_string(),
+            amount,
+        })
+    }
+
+    fn claim(claim: &str) -> RuntimeCall {
+        RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+            claim: PoE::hash_bytes(claim.as_bytes()),
+            description: None,
+            timestamp: None,
+            expires_at: None,
+        })
+    }
+
+    #[test]
+    fn proxy_filters_calls() {
+        let mut runtime = ChainSpec::development().build_runtime();
+        let alice = "Alice".to_string();
+        let bob = "Bob".to_string();
+
+        let res = Proxy::proxy(
+            &mut runtime,
+            bob.clone(),
+            alice.clone(),
+            Box::new(claim("doc")),
+        );
+        assert_eq!(res, Err("Not a proxy"));
+
+        runtime
+            .proxy
+            .add_proxy(alice.clone(), bob.clone(), ProxyType::ClaimsOnly, 0)
+            .unwrap();
+        Proxy::proxy(
+            &mut runtime,
+            bob.clone(),
+            alice.clone(),
+            Box::new(claim("doc")),
+        )
+        .unwrap();
+        assert_eq!(
+            runtime
+                .proof_of_existence
+                .get_claim(&PoE::hash_bytes(b"doc"))
+                .map(|record| &record.owner),
+            Some(&alice)
+        );
+
+        let call = Box::new(transfer("Bob", 10));
+        let res = Proxy::proxy(&mut runtime, bob.clone(), alice.clone(), call);
+        assert_eq!(res, Err("Call filtered by the proxy type"));
+
+        // A batch is only allowed if all of its calls are.
+        runtime
+            .proxy
+            .add_proxy(alice.clone(), bob.clone(), ProxyType::NonTransfer, 0)
+            .unwrap();
+        let calls = vec![claim("other"), transfer("Bob", 10)];
+        let call = Box::new(RuntimeCall::utility(utility::Call::batch { calls }));
+        let res = Proxy::proxy(&mut runtime, bob.clone(), alice.clone(), call);
+        assert_eq!(res, Err("Call filtered by the proxy type"));
+        // Only the deposit of the first claim is held.
+        assert_eq!(runtime.balances.balance(&alice), 99);
+
+        runtime
+            .proxy
+            .remove_proxy(alice.clone(), bob.clone(), ProxyType::ClaimsOnly)
+            .unwrap();
+        let calls = vec![claim("other")];
+        let call = Box::new(RuntimeCall::utility(utility::Call::batch { calls }));
+        Proxy::proxy(&mut runtime, bob.clone(), alice.clone(), call).unwrap();
+    }
+
+    #[test]
+    fn announced_calls_wait_for_the_delay() {
+        let mut runtime = ChainSpec::development().build_runtime();
+        let alice = "Alice".to_string();
+        let bob = "Bob".to_string();
+        runtime
+            .proxy
+            .add_proxy(alice.clone(), bob.clone(), ProxyType::Any, 2)
+            .unwrap();
+
+        let call = Box::new(transfer("Bob", 10));
+        let res = Proxy::proxy(&mut runtime, bob.clone(), alice.clone(), call.clone());
+        assert_eq!(res, Err("Proxy has a delay, announce the call first"));
+
+        let call_hash = Proxy::call_hash(&call);
+        Proxy::announce(&mut runtime, bob.clone(), alice.clone(), call_hash).unwrap();
+        runtime.system.inc_block_number();
+        let res = Proxy::proxy_announced(&mut runtime, bob.clone(), alice.clone(), call.clone());
+        assert_eq!(res, Err("Announcement is not yet due"));
+
+        runtime.system.inc_block_number();
+        Proxy::proxy_announced(&mut runtime, bob.clone(), alice.clone(), call.clone()).unwrap();
+        assert_eq!(runtime.balances.balance(&bob), 10);
+        assert!(runtime.proxy.announcements_of(&bob).is_empty());
+
+        // The real account can reject an announced call.
+        Proxy::announce(&mut runtime, bob.clone(), alice.clone(), call_hash).unwrap();
+        runtime
+            .proxy
+            .reject_announcement(alice.clone(), bob.clone(), call_hash)
+            .unwrap();
+        runtime.system.inc_block_number();
+        runtime.system.inc_block_number();
+        let res = Proxy::proxy_announced(&mut runtime, bob.clone(), alice.clone(), call);
+        assert_eq!(res, Err("Announcement not found"));
+    }
+
+    #[test]
+    fn overflowing_delay_is_never_due() {
+        let mut runtime = ChainSpec::development().build_runtime();
+        let alice = "Alice".to_string();
+        let bob = "Bob".to_string();
+        runtime
+            .proxy
+            .add_proxy(alice.clone(), bob.clone(), ProxyType::Any, u32::MAX)
+            .unwrap();
+
+        let call = Box::new(transfer("Bob", 10));
+        let call_hash = Proxy::call_hash(&call);
+        runtime.system.inc_block_number();
+        Proxy::announce(&mut runtime, bob.clone(), alice.clone(), call_hash).unwrap();
+        runtime.system.inc_block_number();
+        let res = Proxy::proxy_announced(&mut runtime, bob.clone(), alice, call);
+        assert_eq!(res, Err("Announcement is not yet due"));
+        assert_eq!(runtime.balances.balance(&bob), 0);
+    }
+}
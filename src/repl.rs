@@ -0,0 +1,316 @@
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use crate::json::{FromJson, ToJson, Value};
+use crate::node::Node;
+use crate::support::{self, CallMetadata};
+use crate::{Runtime, RuntimeCall, proof_of_existence, types};
+
+/// Shorthands for the most common calls.
+const ALIASES: &[(&str, &str)] = &[
+    ("claim", "create_claim"),
+    ("revoke", "revoke_claim"),
+    ("transfer", "balances.transfer"),
+];
+
+/// Aliases which take the text to claim rather than its hash, e.g. `claim alice my_document`.
+const TEXT_ALIASES: &[&str] = &["claim", "revoke"];
+
+const HELP: &str = "\
+Commands:
+  <call> <caller> <args...>   Queue a call, e.g. `transfer alice bob 30`
+  seal                        Build and execute a block from the queued calls
+  balance <account>           Show the balance of an account
+  nonce <account>             Show the nonce of an account
+  hash <file>                 Show the hash of a file, to claim it
+  state                       Show the whole runtime state
+  help                        Show this message and the available calls
+  exit                        Leave the REPL
+
+Account names are capitalized, so `alice` is the development account `Alice`. Optional arguments
+can be left out at the end, or with `none`. `claim` and `revoke` hash the text they are given,
+while `create_claim` and `revoke_claim` take a hash, e.g. from `hash <file>`.";
+
+/// An interactive shell which queues calls and seals them into blocks on an in-memory node.
+///
+/// Calls are built from `RuntimeCall::metadata`, so the calls of every pallet in the runtime are
+/// available without changes to the REPL.
+pub struct Repl {
+    node: Node,
+}
+
+impl Repl {
+    pub fn new(node: Node) -> Self {
+        Self { node }
+    }
+
+    /// Run the REPL on stdin until `exit` or the end of input.
+    pub fn run(&mut self) -> io::Result<()> {
+        let stdin = io::stdin();
+        let mut stdout = io::stdout();
+        loop {
+            write!(stdout, "> ")?;
+            stdout.flush()?;
+            let mut line = String::new();
+            if stdin.lock().read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            let line = line.trim();
+            if line == "exit" || line == "quit" {
+                return Ok(());
+            }
+            match self.execute(line) {
+                Ok(output) if output.is_empty() => {}
+                Ok(output) => writeln!(stdout, "{}", output)?,
+                Err(e) => writeln!(stdout, "Error: {}", e)?,
+            }
+        }
+    }
+
+    /// Execute a single command, and return what should be printed.
+    pub fn execute(&mut self, line: &str) -> Result<String, String> {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let runtime = self.node.runtime();
+        match words.as_slice() {
+            [] => Ok(String::new()),
+            ["help"] => Ok(format!("{}\n\nCalls:\n{}", HELP, describe_calls())),
+            ["state"] => Ok(format!("{:#?}", runtime)),
+            ["balance", who] => Ok(runtime.balances.balance(&account(who)).to_string()),
+            ["nonce", who] => Ok(runtime.system.nonce(&account(who)).to_string()),
+            ["hash", path] => proof_of_existence::Pallet::<Runtime>::hash_file(Path::new(path))
+                .map(|hash| hash.to_string())
+                .map_err(|e| e.to_string()),
+            ["seal"] => {
+                let count = self.node.pending_extrinsics();
+                self.node.author_block()?;
+                let mut output = format!(
+                    "Sealed block #{} with {} extrinsics",
+                    self.node.best_number(),
+                    count
+                );
+                let receipt = self.node.receipt(self.node.best_number()).unwrap();
+                for (i, extrinsic) in receipt.extrinsics.iter().enumerate() {
+                    let outcome = match extrinsic.result {
+                        Ok(()) => "ok".to_string(),
+                        Err(e) => format!("failed: {}", e),
+                    };
+                    output += &format!("\n  #{} {}, fee {}", i, outcome, extrinsic.fee);
+                    for event in &extrinsic.events {
+                        output += &format!("\n    {}", event.to_json());
+                    }
+                }
+                Ok(output)
+            }
+            [name, caller, args @ ..] => {
+                let call = build_call(name, args)?;
+                self.node.submit_extrinsic(support::Extrinsic {
+                    caller: account(caller),
+                    call,
+                });
+                Ok(format!(
+                    "Queued, {} pending",
+                    self.node.pending_extrinsics()
+                ))
+            }
+            [name] => Err(format!("Unknown command {}, try `help`", name)),
+        }
+    }
+}
+
+/// Capitalize an account name, so `alice` refers to `Alice`.
+fn account(name: &str) -> types::AccountId {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Find a call by its name, or by `pallet.call` if the name is used by several pallets.
+fn find_call(name: &str) -> Result<(&'static str, CallMetadata), String> {
+    let name = ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, call)| call);
+    let (pallet, call) = match name.split_once('.') {
+        Some((pallet, call)) => (Some(pallet), call),
+        None => (None, name),
+    };
+
+    let mut found = RuntimeCall::metadata()
+        .into_iter()
+        .filter(|p| pallet.is_none_or(|pallet| pallet == p.name))
+        .flat_map(|p| p.calls.into_iter().map(move |c| (p.name, c)))
+        .filter(|(_, c)| c.name == call)
+        .collect::<Vec<_>>();
+    match found.len() {
+        0 => Err(format!("Unknown call {}, try `help`", name)),
+        1 => Ok(found.remove(0)),
+        _ => Err(format!("Ambiguous call {}, use `pallet.call`", name)),
+    }
+}
+
+/// Build a call from its name and its arguments as typed in the REPL. Missing optional arguments
+/// at the end are `None`.
+fn build_call(name: &str, args: &[&str]) -> Result<RuntimeCall, String> {
+    let hash_text = TEXT_ALIASES.contains(&name);
+    let (pallet, call) = find_call(name)?;
+    let required = call
+        .args
+        .iter()
+        .rposition(|arg| !arg.type_name.starts_with("Option"))
+        .map_or(0, |last| last + 1);
+    if args.len() < required || args.len() > call.args.len() {
+        let expected = call.args.iter().map(|arg| arg.name).collect::<Vec<_>>();
+        return Err(format!(
+            "{} expects {} arguments: {}",
+            call.name,
+            expected.len(),
+            expected.join(" ")
+        ));
+    }
+
+    let args = call
+        .args
+        .iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            let Some(&value) = args.get(i) else {
+                return (arg.name.to_string(), Value::Null);
+            };
+            let value = if i == 0 && hash_text {
+                proof_of_existence::Pallet::<Runtime>::hash_bytes(value.as_bytes()).to_json()
+            } else if arg.type_name == "T::AccountId" {
+                Value::String(account(value))
+            } else if arg.type_name.starts_with("Option") && value == "none" {
+                Value::Null
+            } else if value.parse::<u128>().is_ok() {
+                Value::Number(value.to_string())
+            } else {
+                Value::String(value.to_string())
+            };
+            (arg.name.to_string(), value)
+        })
+        .collect();
+    let call = Value::object([(call.name, Value::Object(args))]);
+    RuntimeCall::from_json(&Value::object([(pallet, call)])).map_err(str::to_string)
+}
+
+fn describe_calls() -> String {
+    let mut lines = Vec::new();
+    for pallet in RuntimeCall::metadata() {
+        for call in pallet.calls {
+            let args = call
+                .args
+                .iter()
+                .map(|arg| format!("<{}: {}>", arg.name, arg.type_name))
+                .collect::<Vec<_>>();
+            lines.push(format!(
+                "  {}.{} <caller> {}",
+                pallet.name,
+                call.name,
+                args.join(" ")
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Repl;
+    use crate::chain_spec::ChainSpec;
+    use crate::hashing::sha256;
+    use crate::node::Node;
+
+    fn new_repl() -> Repl {
+        Repl::new(Node::new(ChainSpec::development().build_runtime()))
+    }
+
+    #[test]
+    fn drive_runtime() {
+        let mut repl = new_repl();
+
+        assert_eq!(
+            repl.execute("transfer alice bob 30").unwrap(),
+            "Queued, 1 pending"
+        );
+        assert_eq!(
+            repl.execute("claim alice my_document").unwrap(),
+            "Queued, 2 pending"
+        );
+        assert_eq!(repl.execute("balance bob").unwrap(), "0");
+
+        assert_eq!(
+            repl.execute("seal").unwrap(),
+            format!(
+                "Sealed block #1 with 2 extrinsics\n  \
+                #0 ok, fee 2\n    \
+                {{\"balances\":{{\"Transfer\":{{\"amount\":30,\"from\":\"Alice\",\"to\":\"Bob\"}}}}}}\n  \
+                #1 ok, fee 1\n    \
+                {{\"balances\":{{\"Reserved\":{{\"amount\":1,\"who\":\"Alice\"}}}}}}\n    \
+                {{\"proof_of_existence\":{{\"ClaimCreated\":{{\"claim\":\"{}\",\"owner\":\"Alice\"}}}}}}",
+                sha256(b"my_document")
+            )
+        );
+        assert_eq!(repl.execute("balance bob").unwrap(), "30");
+        assert_eq!(repl.execute("nonce alice").unwrap(), "2");
+    }
+
+    #[test]
+    fn claim_files() {
+        let mut repl = new_repl();
+        let path = std::env::temp_dir().join("rsm-repl-my_file");
+        std::fs::write(&path, "my_file").unwrap();
+        let hash = repl.execute(&format!("hash {}", path.display())).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(hash, sha256(b"my_file").to_string());
+
+        // The full call takes the hash, and its optional arguments can be left out.
+        repl.execute(&format!("create_claim alice {} none", hash))
+            .unwrap();
+        repl.execute("seal").unwrap();
+        let runtime = repl.node.runtime();
+        let record = runtime.proof_of_existence.get_claim(&sha256(b"my_file"));
+        assert_eq!(record.unwrap().owner, "Alice");
+
+        repl.execute("revoke alice my_file").unwrap();
+        repl.execute("seal").unwrap();
+        let runtime = repl.node.runtime();
+        assert_eq!(
+            runtime.proof_of_existence.get_claim(&sha256(b"my_file")),
+            None
+        );
+    }
+
+    #[test]
+    fn invalid_commands() {
+        let mut repl = new_repl();
+
+        assert_eq!(
+            repl.execute("transfer alice bob"),
+            Err("transfer expects 2 arguments: to amount".to_string())
+        );
+        assert_eq!(
+            repl.execute("teleport alice 10"),
+            Err("Unknown call teleport, try `help`".to_string())
+        );
+        assert_eq!(
+            repl.execute("mint alice 10"),
+            Err("mint expects 3 arguments: asset_id beneficiary amount".to_string())
+        );
+        assert_eq!(
+            repl.execute("burn alice 10"),
+            Err("Ambiguous call burn, use `pallet.call`".to_string())
+        );
+        assert_eq!(
+            repl.execute("balances.create_claim alice doc"),
+            Err("Unknown call balances.create_claim, try `help`".to_string())
+        );
+        assert_eq!(
+            repl.execute("frobnicate"),
+            Err("Unknown command frobnicate, try `help`".to_string())
+        );
+        assert!(repl.execute("help").unwrap().contains("balances.transfer"));
+    }
+}
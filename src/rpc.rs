@@ -0,0 +1,694 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use std::{process, thread};
+
+use crate::json::{FromJson, ToJson, Value};
+use crate::log;
+use crate::node::Node;
+use crate::support::{DispatchResult, ReservableCurrency};
+use crate::types;
+
+// Standard JSON-RPC 2.0 error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// A method which is disabled unless the node allows unsafe methods.
+const UNSAFE_METHOD: i64 = -32001;
+
+/// The longest request line the server reads, in bytes. Longer requests close the connection.
+const MAX_REQUEST_LENGTH: usize = 1 << 20;
+
+struct RpcError {
+    code: i64,
+    message: &'static str,
+}
+
+impl RpcError {
+    fn new(code: i64, message: &'static str) -> Self {
+        Self { code, message }
+    }
+}
+
+/// A client subscription, created by one of the `*_subscribe*` methods.
+struct Subscription {
+    id: u64,
+    kind: SubscriptionKind,
+    connection: Connection,
+}
+
+#[derive(PartialEq)]
+enum SubscriptionKind {
+    NewHeads,
+    FinalizedHeads,
+    Events(EventFilter),
+}
+
+/// Restricts an event subscription to the events of one pallet, or which concern one account.
+#[derive(PartialEq, Default)]
+struct EventFilter {
+    pallet: Option<String>,
+    account: Option<types::AccountId>,
+}
+
+impl EventFilter {
+    fn matches(&self, event: &crate::RuntimeEvent) -> bool {
+        let pallet = self.pallet.as_ref().is_none_or(|p| p == event.pallet());
+        let account = self
+            .account
+            .as_ref()
+            .is_none_or(|who| event.accounts().contains(&who));
+        pallet && account
+    }
+}
+
+impl FromJson for EventFilter {
+    fn from_json(value: &Value) -> Result<Self, &'static str> {
+        match value {
+            Value::Null => Ok(EventFilter::default()),
+            Value::Object(_) => Ok(EventFilter {
+                pallet: Option::from_json(value.get("pallet").unwrap_or(&Value::Null))?,
+                account: Option::from_json(value.get("account").unwrap_or(&Value::Null))?,
+            }),
+            _ => Err("Expected an event filter object"),
+        }
+    }
+}
+
+/// The sending half of a client connection. Responses and notifications are sent through it, and
+/// written to the socket in order.
+#[derive(Clone)]
+pub struct Connection {
+    id: u64,
+    sender: Sender<String>,
+}
+
+impl Connection {
+    fn send(&self, message: String) -> bool {
+        self.sender.send(message).is_ok()
+    }
+}
+
+/// The node, along with the subscriptions of every connected client.
+pub struct Server {
+    node: Node,
+    subscriptions: Vec<Subscription>,
+    next_id: u64,
+    /// Whether extrinsics can be submitted. Extrinsics are not signed, so whoever submits one
+    /// can make calls as any account.
+    unsafe_methods: bool,
+}
+
+impl Server {
+    pub fn new(node: Node, unsafe_methods: bool) -> Self {
+        Self {
+            node,
+            subscriptions: Vec::new(),
+            next_id: 0,
+            unsafe_methods,
+        }
+    }
+
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    pub fn connect(&mut self, sender: Sender<String>) -> Connection {
+        Connection {
+            id: self.next_id(),
+            sender,
+        }
+    }
+
+    /// Drop all the subscriptions of a closed connection.
+    pub fn disconnect(&mut self, connection: &Connection) {
+        self.subscriptions
+            .retain(|sub| sub.connection.id != connection.id);
+    }
+
+    fn subscribe(&mut self, connection: &Connection, kind: SubscriptionKind) -> u64 {
+        let id = self.next_id();
+        self.subscriptions.push(Subscription {
+            id,
+            kind,
+            connection: connection.clone(),
+        });
+        id
+    }
+
+    fn unsubscribe(&mut self, connection: &Connection, id: u64) -> bool {
+        let len = self.subscriptions.len();
+        self.subscriptions
+            .retain(|sub| !(sub.id == id && sub.connection.id == connection.id));
+        self.subscriptions.len() != len
+    }
+
+    /// Author a block from the pool, and notify subscribers about it.
+    pub fn author_block(&mut self) -> DispatchResult {
+        self.node.author_block()?;
+        log::info!("Authored block #{}", self.node.best_number());
+        self.notify_best_block();
+        Ok(())
+    }
+
+    /// Push the latest block to every subscriber. Subscriptions whose connection was closed are
+    /// dropped.
+    ///
+    /// This node is the only block author and never reorganizes the chain, so each block is
+    /// final as soon as it is imported.
+    fn notify_best_block(&mut self) {
+        let Some(block) = self.node.block(self.node.best_number()) else {
+            return;
+        };
+        let header = block.header.to_json();
+        let events = &self.node.runtime().system.events();
+
+        self.subscriptions.retain(|sub| {
+            let (method, result) = match &sub.kind {
+                SubscriptionKind::NewHeads => ("chain_newHead", header.clone()),
+                SubscriptionKind::FinalizedHeads => ("chain_finalizedHead", header.clone()),
+                SubscriptionKind::Events(filter) => {
+                    let events = events
+                        .iter()
+                        .filter(|record| filter.matches(&record.event))
+                        .map(|record| {
+                            Value::object([
+                                ("extrinsic_index", record.extrinsic_index.to_json()),
+                                ("event", record.event.to_json()),
+                            ])
+                        })
+                        .collect::<Vec<_>>();
+                    if events.is_empty() {
+                        return true;
+                    }
+                    ("state_events", Value::Array(events))
+                }
+            };
+            sub.connection.send(notification(method, sub.id, result))
+        });
+    }
+}
+
+/// Serve JSON-RPC requests over TCP, one JSON request per line.
+///
+/// Extrinsics submitted with `author_submitExtrinsic` are collected in the pool, and a new block
+/// is authored from them every `block_time`. Clients can subscribe to new blocks and events, and
+/// are notified over the same connection.
+///
+/// Extrinsics are not signed: the `caller` of a submitted extrinsic is trusted as is, so any
+/// client can make calls as any account. Submitting is therefore only allowed with
+/// `unsafe_methods`, and the server must then never be reachable by untrusted clients.
+///
+/// A panic while handling a request or authoring a block may leave the state half updated, so
+/// the node shuts down instead of serving it. Imported blocks are stored in the database, and
+/// restarting the node restores the state of the last one.
+pub fn serve(
+    node: Node,
+    addr: impl ToSocketAddrs,
+    block_time: Duration,
+    unsafe_methods: bool,
+) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    let server = Arc::new(Mutex::new(Server::new(node, unsafe_methods)));
+
+    let author = Arc::clone(&server);
+    thread::spawn(move || {
+        loop {
+            thread::sleep(block_time);
+            let mut server = lock(&author);
+            if server.node.pending_extrinsics() > 0
+                && let Err(e) = server.author_block()
+            {
+                log::error!("Block authoring failed: {}", e);
+            }
+        }
+    });
+
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let server = Arc::clone(&server);
+        thread::spawn(move || {
+            if let Err(e) = handle_connection(&server, stream) {
+                log::warning!("RPC connection error: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Take the lock of the server, or stop the process if a panic poisoned it.
+fn lock(server: &Mutex<Server>) -> MutexGuard<'_, Server> {
+    server.lock().unwrap_or_else(|_| {
+        log::error!("A panic left the node in an unknown state, shutting down");
+        process::exit(1)
+    })
+}
+
+fn handle_connection(server: &Mutex<Server>, stream: TcpStream) -> io::Result<()> {
+    let (sender, receiver) = mpsc::channel::<String>();
+    let connection = lock(server).connect(sender);
+
+    // Responses and notifications are written by a separate thread, which stops once the
+    // connection and all of its subscriptions are dropped.
+    let mut writer = stream.try_clone()?;
+    thread::spawn(move || {
+        for message in receiver {
+            if writeln!(writer, "{}", message).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut result = Ok(());
+    let mut reader = BufReader::new(stream);
+    loop {
+        // Reading stops after `MAX_REQUEST_LENGTH` bytes, so a client can't grow the line without
+        // bound.
+        let mut line = String::new();
+        let limit = MAX_REQUEST_LENGTH as u64 + 1;
+        match (&mut reader).take(limit).read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        }
+        if line.len() > MAX_REQUEST_LENGTH {
+            let error = RpcError::new(INVALID_REQUEST, "Request too large");
+            connection.send(response(Value::Null, Err(error)));
+            break;
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        // The response is sent while holding the lock, so a subscription response is always
+        // sent before its first notification.
+        log::debug!("RPC request: {}", line);
+        let mut server = lock(server);
+        let response = handle_request(&mut server, &connection, line);
+        connection.send(response);
+    }
+
+    lock(server).disconnect(&connection);
+    result
+}
+
+/// Handle a single JSON-RPC request from `connection` and return the encoded response.
+pub fn handle_request(server: &mut Server, connection: &Connection, request: &str) -> String {
+    let request = match Value::parse(request) {
+        Ok(request) => request,
+        Err(e) => return response(Value::Null, Err(RpcError::new(PARSE_ERROR, e))),
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+
+    let method = match request.get("method").and_then(Value::as_str) {
+        Some(method) => method,
+        None => {
+            let error = RpcError::new(INVALID_REQUEST, "Missing method");
+            return response(id, Err(error));
+        }
+    };
+    let params = match request.get("params") {
+        Some(Value::Array(params)) => params.as_slice(),
+        None => &[],
+        Some(_) => {
+            let error = RpcError::new(INVALID_REQUEST, "Params must be an array");
+            return response(id, Err(error));
+        }
+    };
+
+    response(id, call_method(server, connection, method, params))
+}
+
+fn call_method(
+    server: &mut Server,
+    connection: &Connection,
+    method: &str,
+    params: &[Value],
+) -> Result<Value, RpcError> {
+    let node = &mut server.node;
+    match method {
+        "chain_getBlock" => {
+            let number = param::<Option<types::BlockNumber>>(params, 0)?;
+            let block = node.block(number.unwrap_or(node.best_number()));
+            Ok(block.to_json())
+        }
+        "chain_getHeader" => {
+            let number = param::<Option<types::BlockNumber>>(params, 0)?;
+            let block = node.block(number.unwrap_or(node.best_number()));
+            Ok(block.map(|block| &block.header).to_json())
+        }
+        "chain_getReceipt" => {
+            let number = param::<types::BlockNumber>(params, 0)?;
+            let index = param::<Option<usize>>(params, 1)?;
+            let receipt = node.receipt(number);
+            Ok(match index {
+                Some(index) => receipt
+                    .and_then(|receipt| receipt.extrinsics.get(index))
+                    .to_json(),
+                None => receipt.to_json(),
+            })
+        }
+        "state_getBalance" => {
+            let who = param::<types::AccountId>(params, 0)?;
+            Ok(node.runtime().balances.balance(&who).to_json())
+        }
+        "state_getAccount" => {
+            let who = param::<types::AccountId>(params, 0)?;
+            let balances = &node.runtime().balances;
+            Ok(Value::object([
+                ("free", balances.balance(&who).to_json()),
+                ("reserved", balances.reserved_balance(&who).to_json()),
+            ]))
+        }
+        "state_getAllowance" => {
+            let owner = param::<types::AccountId>(params, 0)?;
+            let delegate = param::<types::AccountId>(params, 1)?;
+            Ok(node
+                .runtime()
+                .balances
+                .allowance(&owner, &delegate)
+                .to_json())
+        }
+        "state_getTotalIssuance" => Ok(node.runtime().balances.total_issuance().to_json()),
+        "state_getNonce" => {
+            let who = param::<types::AccountId>(params, 0)?;
+            Ok(node.runtime().system.nonce(&who).to_json())
+        }
+        "assets_getAsset" => {
+            let asset_id = param::<types::AssetId>(params, 0)?;
+            let assets = &node.runtime().assets;
+            Ok(match assets.asset(asset_id) {
+                Some(details) => Value::object([
+                    ("details", details.to_json()),
+                    ("metadata", assets.metadata(asset_id).to_json()),
+                ]),
+                None => Value::Null,
+            })
+        }
+        "assets_getBalance" => {
+            let asset_id = param::<types::AssetId>(params, 0)?;
+            let who = param::<types::AccountId>(params, 1)?;
+            Ok(node.runtime().assets.balance(asset_id, &who).to_json())
+        }
+        "poe_getClaim" => {
+            let claim = param::<types::Content>(params, 0)?;
+            Ok(node
+                .runtime()
+                .proof_of_existence
+                .get_claim(&claim)
+                .to_json())
+        }
+        "poe_getClaimsOf" => {
+            let who = param::<types::AccountId>(params, 0)?;
+            let start_after = param::<Option<types::Content>>(params, 1)?;
+            let limit = param::<Option<u32>>(params, 2)?.unwrap_or(100);
+            let claims = node.runtime().proof_of_existence.claims_of(
+                &who,
+                start_after.as_ref(),
+                limit as usize,
+            );
+            Ok(claims.to_json())
+        }
+        "poe_verifyBatchClaim" => {
+            let document = param::<types::Content>(params, 0)?;
+            let path = param::<Vec<types::Content>>(params, 1)?;
+            let root = param::<types::Content>(params, 2)?;
+            Ok(node
+                .runtime()
+                .proof_of_existence
+                .verify_batch_claim(&document, &path, &root)
+                .to_json())
+        }
+        "identity_getIdentity" => {
+            let who = param::<types::AccountId>(params, 0)?;
+            Ok(node.runtime().identity.identity(&who).to_json())
+        }
+        "author_submitExtrinsic" => {
+            if !server.unsafe_methods {
+                return Err(RpcError::new(
+                    UNSAFE_METHOD,
+                    "Unsafe method, the node must be started with --rpc-unsafe",
+                ));
+            }
+            let extrinsic = param::<types::Extrinsic>(params, 0)?;
+            node.submit_extrinsic(extrinsic);
+            Ok(Value::Bool(true))
+        }
+        "chain_subscribeNewHeads" => Ok(server
+            .subscribe(connection, SubscriptionKind::NewHeads)
+            .to_json()),
+        "chain_subscribeFinalizedHeads" => {
+            let kind = SubscriptionKind::FinalizedHeads;
+            Ok(server.subscribe(connection, kind).to_json())
+        }
+        "state_subscribeEvents" => {
+            let filter = param::<EventFilter>(params, 0)?;
+            let kind = SubscriptionKind::Events(filter);
+            Ok(server.subscribe(connection, kind).to_json())
+        }
+        "chain_unsubscribeNewHeads"
+        | "chain_unsubscribeFinalizedHeads"
+        | "state_unsubscribeEvents" => {
+            let id = param::<u64>(params, 0)?;
+            Ok(server.unsubscribe(connection, id).to_json())
+        }
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, "Method not found")),
+    }
+}
+
+/// Decode the positional parameter at `index`. A missing parameter is decoded as `null`.
+fn param<T: FromJson>(params: &[Value], index: usize) -> Result<T, RpcError> {
+    T::from_json(params.get(index).unwrap_or(&Value::Null))
+        .map_err(|e| RpcError::new(INVALID_PARAMS, e))
+}
+
+fn notification(method: &str, subscription: u64, result: Value) -> String {
+    Value::object([
+        ("jsonrpc", "2.0".to_json()),
+        ("method", method.to_json()),
+        (
+            "params",
+            Value::object([("subscription", subscription.to_json()), ("result", result)]),
+        ),
+    ])
+    .to_string()
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> String {
+    let outcome = match result {
+        Ok(result) => ("result", result),
+        Err(RpcError { code, message }) => (
+            "error",
+            Value::object([
+                ("code", Value::Number(code.to_string())),
+                ("message", message.to_json()),
+            ]),
+        ),
+    };
+    Value::object([("jsonrpc", "2.0".to_json()), ("id", id), outcome]).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Connection, Server};
+    use crate::Runtime;
+    use crate::node::Node;
+    use std::sync::mpsc::{self, Receiver};
+
+    fn new_server() -> (Server, Connection, Receiver<String>) {
+        let mut runtime = Runtime::new();
+        runtime.balances.set_balance(&"Alice".to_string(), 100);
+        let mut server = Server::new(Node::new(runtime), true);
+        let (sender, receiver) = mpsc::channel();
+        let connection = server.connect(sender);
+        (server, connection, receiver)
+    }
+
+    #[test]
+    fn query_state() {
+        let (mut server, connection, _) = new_server();
+
+        let res = super::handle_request(
+            &mut server,
+            &connection,
+            r#"{"jsonrpc":"2.0","id":1,"method":"state_getBalance","params":["Alice"]}"#,
+        );
+        assert_eq!(res, r#"{"id":1,"jsonrpc":"2.0","result":100}"#);
+
+        let res = super::handle_request(
+            &mut server,
+            &connection,
+            r#"{"jsonrpc":"2.0","id":2,"method":"chain_getHeader","params":[]}"#,
+        );
+        assert_eq!(res, r#"{"id":2,"jsonrpc":"2.0","result":null}"#);
+    }
+
+    #[test]
+    fn submit_extrinsic() {
+        let (mut server, connection, _) = new_server();
+
+        let res = super::handle_request(
+            &mut server,
+            &connection,
+            r#"{"jsonrpc":"2.0","id":1,"method":"author_submitExtrinsic","params":[
+                {"caller":"Alice","call":{"balances":{"transfer":{"to":"Bob","amount":30}}}}
+            ]}"#,
+        );
+        assert_eq!(res, r#"{"id":1,"jsonrpc":"2.0","result":true}"#);
+        server.author_block().unwrap();
+
+        let res = super::handle_request(
+            &mut server,
+            &connection,
+            r#"{"jsonrpc":"2.0","id":2,"method":"chain_getBlock","params":[1]}"#,
+        );
+        assert_eq!(
+            res,
+            r#"{"id":2,"jsonrpc":"2.0","result":{"extrinsics":[{"call":{"balances":{"transfer":{"amount":30,"to":"Bob"}}},"caller":"Alice"}],"header":{"block_number":1}}}"#
+        );
+
+        let res = super::handle_request(
+            &mut server,
+            &connection,
+            r#"{"jsonrpc":"2.0","id":3,"method":"state_getNonce","params":["Alice"]}"#,
+        );
+        assert_eq!(res, r#"{"id":3,"jsonrpc":"2.0","result":1}"#);
+
+        let res = super::handle_request(
+            &mut server,
+            &connection,
+            r#"{"jsonrpc":"2.0","id":4,"method":"chain_getReceipt","params":[1, 0]}"#,
+        );
+        assert_eq!(
+            res,
+            r#"{"id":4,"jsonrpc":"2.0","result":{"error":null,"events":[{"balances":{"Transfer":{"amount":30,"from":"Alice","to":"Bob"}}}],"fee":2,"success":true,"weight":2}}"#
+        );
+
+        let res = super::handle_request(
+            &mut server,
+            &connection,
+            r#"{"jsonrpc":"2.0","id":5,"method":"state_getBalance","params":["Alice"]}"#,
+        );
+        assert_eq!(res, r#"{"id":5,"jsonrpc":"2.0","result":68}"#);
+
+        let res = super::handle_request(
+            &mut server,
+            &connection,
+            r#"{"jsonrpc":"2.0","id":6,"method":"state_getAccount","params":["Bob"]}"#,
+        );
+        assert_eq!(
+            res,
+            r#"{"id":6,"jsonrpc":"2.0","result":{"free":30,"reserved":0}}"#
+        );
+
+        let res = super::handle_request(
+            &mut server,
+            &connection,
+            r#"{"jsonrpc":"2.0","id":7,"method":"state_getTotalIssuance","params":[]}"#,
+        );
+        assert_eq!(res, r#"{"id":7,"jsonrpc":"2.0","result":98}"#);
+    }
+
+    #[test]
+    fn subscriptions() {
+        let (mut server, connection, receiver) = new_server();
+
+        let res = super::handle_request(
+            &mut server,
+            &connection,
+            r#"{"id":1,"method":"chain_subscribeNewHeads"}"#,
+        );
+        assert_eq!(res, r#"{"id":1,"jsonrpc":"2.0","result":2}"#);
+        let res = super::handle_request(
+            &mut server,
+            &connection,
+            r#"{"id":2,"method":"state_subscribeEvents","params":[{"account":"Charlie"}]}"#,
+        );
+        assert_eq!(res, r#"{"id":2,"jsonrpc":"2.0","result":3}"#);
+
+        // Only the new head is pushed, as no event concerns Charlie.
+        server.node.submit_extrinsic(crate::support::Extrinsic {
+            caller: "Alice".to_string(),
+            call: crate::RuntimeCall::balances(crate::balances::Call::transfer {
+                to: "Bob".to_string(),
+                amount: 30,
+            }),
+        });
+        server.author_block().unwrap();
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            r#"{"jsonrpc":"2.0","method":"chain_newHead","params":{"result":{"block_number":1},"subscription":2}}"#
+        );
+        assert!(receiver.try_recv().is_err());
+
+        let res = super::handle_request(
+            &mut server,
+            &connection,
+            r#"{"id":3,"method":"chain_unsubscribeNewHeads","params":[2]}"#,
+        );
+        assert_eq!(res, r#"{"id":3,"jsonrpc":"2.0","result":true}"#);
+
+        server.node.submit_extrinsic(crate::support::Extrinsic {
+            caller: "Alice".to_string(),
+            call: crate::RuntimeCall::balances(crate::balances::Call::transfer {
+                to: "Charlie".to_string(),
+                amount: 20,
+            }),
+        });
+        server.author_block().unwrap();
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            r#"{"jsonrpc":"2.0","method":"state_events","params":{"result":[{"event":{"balances":{"Transfer":{"amount":20,"from":"Alice","to":"Charlie"}}},"extrinsic_index":0}],"subscription":3}}"#
+        );
+
+        server.disconnect(&connection);
+        assert!(server.subscriptions.is_empty());
+    }
+
+    #[test]
+    fn invalid_requests() {
+        let (mut server, connection, _) = new_server();
+
+        let res = super::handle_request(&mut server, &connection, r#"{"id":"#);
+        assert_eq!(
+            res,
+            r#"{"error":{"code":-32700,"message":"Unexpected end of input"},"id":null,"jsonrpc":"2.0"}"#
+        );
+
+        let res = super::handle_request(&mut server, &connection, r#"{"id":1,"method":"foo"}"#);
+        assert_eq!(
+            res,
+            r#"{"error":{"code":-32601,"message":"Method not found"},"id":1,"jsonrpc":"2.0"}"#
+        );
+
+        let res = super::handle_request(
+            &mut server,
+            &connection,
+            r#"{"id":1,"method":"author_submitExtrinsic","params":[{"caller":"Alice","call":{"balances":{"mint":{}}}}]}"#,
+        );
+        assert_eq!(
+            res,
+            r#"{"error":{"code":-32602,"message":"Unknown call"},"id":1,"jsonrpc":"2.0"}"#
+        );
+
+        // Extrinsics can only be submitted when unsafe methods are allowed.
+        server.unsafe_methods = false;
+        let res = super::handle_request(
+            &mut server,
+            &connection,
+            r#"{"id":1,"method":"author_submitExtrinsic","params":[{"caller":"Alice","call":{"balances":{"transfer":{"to":"Bob","amount":30}}}}]}"#,
+        );
+        assert_eq!(
+            res,
+            r#"{"error":{"code":-32001,"message":"Unsafe method, the node must be started with --rpc-unsafe"},"id":1,"jsonrpc":"2.0"}"#
+        );
+        assert_eq!(server.node.pending_extrinsics(), 0);
+    }
+}
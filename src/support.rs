@@ -1,21 +1,287 @@
+use crate::json::{FromJson, ToJson, Value};
+
+#[derive(Clone)]
 pub struct Block<Header, Extrinsic> {
     pub header: Header,
     pub extrinsics: Vec<Extrinsic>,
 }
 
+#[derive(Clone)]
 pub struct Header<BlockNumber> {
     pub block_number: BlockNumber,
 }
 
+#[derive(Clone)]
 pub struct Extrinsic<Caller, Call> {
     pub caller: Caller,
     pub call: Call,
 }
 
-pub type DispatchResult = Result<(), &'static str>;
+pub type DispatchError = &'static str;
+
+pub type DispatchResult = Result<(), DispatchError>;
+
+/// The cost of executing a call, used to compute its fee.
+pub type Weight = u64;
+
+/// The weight of calls which do not declare one with `#[weight(...)]`.
+pub const DEFAULT_WEIGHT: Weight = 1;
+
+/// Anything with a weight, such as the calls of the runtime.
+pub trait GetWeight {
+    fn weight(&self) -> Weight;
+}
+
+/// The number of items a pallet holds in storage. Calls which copy the whole state, such as
+/// `utility::batch_all`, are charged for it.
+pub trait StorageSize {
+    fn storage_size(&self) -> usize;
+}
 
 pub trait Dispatch {
     type Caller;
     type Call;
     fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> DispatchResult;
 }
+
+/// Functions which a pallet can run at block boundaries. Like a call taking the runtime, a hook
+/// reaches itself and the other pallets through `AsMut`.
+pub trait Hooks<Runtime> {
+    /// Called after every extrinsic of the block has been executed.
+    fn on_finalize(_runtime: &mut Runtime) {}
+}
+
+/// Charge the fee for an extrinsic before it is dispatched. This is implemented by the runtime,
+/// which knows where the funds of the caller are held.
+pub trait ChargeFee {
+    type Caller;
+    type Balance;
+    /// Withdraw the fee for `weight` from `who`, and return the fee paid.
+    fn charge_fee(
+        &mut self,
+        who: &Self::Caller,
+        weight: Weight,
+    ) -> Result<Self::Balance, DispatchError>;
+}
+
+/// Funds held by accounts. A pallet which needs to move funds declares
+/// `type Currency: Currency<Self::AccountId>` in its `Config`, rather than depending on a specific
+/// pallet, so fees, deposits and rewards work with any currency.
+// Not every method is used by the pallets of the runtime yet.
+#[allow(dead_code)]
+pub trait Currency<AccountId> {
+    type Balance;
+    /// The free balance of `who`, which is what it can spend.
+    fn balance_of(&self, who: &AccountId) -> Self::Balance;
+    fn total_issuance(&self) -> Self::Balance;
+    /// Check that `amount` can be withdrawn from the free balance of `who` for `reasons`.
+    fn can_withdraw(
+        &self,
+        who: &AccountId,
+        amount: Self::Balance,
+        reasons: WithdrawReasons,
+        existence: ExistenceRequirement,
+    ) -> DispatchResult;
+    fn transfer(
+        &mut self,
+        from: &AccountId,
+        to: &AccountId,
+        amount: Self::Balance,
+        existence: ExistenceRequirement,
+    ) -> DispatchResult;
+    /// Destroy `amount` from the free balance of `who`, reducing the total issuance.
+    fn withdraw(
+        &mut self,
+        who: &AccountId,
+        amount: Self::Balance,
+        reasons: WithdrawReasons,
+        existence: ExistenceRequirement,
+    ) -> DispatchResult;
+    /// Create `amount` in the free balance of `who`, increasing the total issuance.
+    fn deposit(&mut self, who: &AccountId, amount: Self::Balance) -> DispatchResult;
+}
+
+/// A currency where part of the balance of an account can be set aside, for example as a
+/// deposit. Reserved funds still belong to the account, but can not be spent until they are
+/// unreserved.
+// Not every method is used by the pallets of the runtime yet.
+#[allow(dead_code)]
+pub trait ReservableCurrency<AccountId>: Currency<AccountId> {
+    fn reserved_balance(&self, who: &AccountId) -> Self::Balance;
+    /// Move `amount` from the free balance of `who` to its reserved balance.
+    fn reserve(&mut self, who: &AccountId, amount: Self::Balance) -> DispatchResult;
+    /// Move up to `amount` from the reserved balance of `who` back to its free balance, and return
+    /// the amount which could not be unreserved.
+    fn unreserve(&mut self, who: &AccountId, amount: Self::Balance) -> Self::Balance;
+    /// Destroy up to `amount` of the reserved balance of `who`, and return the amount which could
+    /// not be slashed.
+    fn slash_reserved(&mut self, who: &AccountId, amount: Self::Balance) -> Self::Balance;
+    /// Move up to `amount` from the reserved balance of `slashed` to the free balance of
+    /// `beneficiary`, and return the amount which could not be moved. Fails if `beneficiary` would
+    /// be a new account below the existential deposit.
+    fn repatriate_reserved(
+        &mut self,
+        slashed: &AccountId,
+        beneficiary: &AccountId,
+        amount: Self::Balance,
+    ) -> Result<Self::Balance, DispatchError>;
+}
+
+/// Whether a withdrawal may leave an account below the existential deposit, which removes it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExistenceRequirement {
+    KeepAlive,
+    AllowDeath,
+}
+
+/// Identifies the pallet or feature which placed a lock, e.g. `*b"vesting "`.
+pub type LockIdentifier = [u8; 8];
+
+/// The kind of withdrawal a lock applies to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WithdrawReasons {
+    /// Paying the fee of an extrinsic.
+    Fee,
+    /// Anything else, such as transferring or reserving funds.
+    Misc,
+    All,
+}
+
+impl WithdrawReasons {
+    /// Whether a lock for these reasons applies to a withdrawal for `other`.
+    pub fn includes(self, other: WithdrawReasons) -> bool {
+        self == WithdrawReasons::All || other == WithdrawReasons::All || self == other
+    }
+}
+
+/// A currency where part of the free balance of an account can be frozen without moving it.
+/// Overlapping locks are not added up, the largest one applies.
+// Not every method is used by the pallets of the runtime yet.
+#[allow(dead_code)]
+pub trait LockableCurrency<AccountId>: Currency<AccountId> {
+    /// Lock `amount` of the free balance of `who`, replacing any lock with the same `id`.
+    fn set_lock(
+        &mut self,
+        id: LockIdentifier,
+        who: &AccountId,
+        amount: Self::Balance,
+        reasons: WithdrawReasons,
+    );
+    /// Like `set_lock`, but an existing lock is only ever made larger: the amount is the largest
+    /// of both, and the reasons are combined.
+    fn extend_lock(
+        &mut self,
+        id: LockIdentifier,
+        who: &AccountId,
+        amount: Self::Balance,
+        reasons: WithdrawReasons,
+    );
+    fn remove_lock(&mut self, id: LockIdentifier, who: &AccountId);
+}
+
+/// The outcome of executing each extrinsic of a block.
+#[derive(Debug, Clone)]
+pub struct BlockReceipt<BlockNumber, Balance, Event> {
+    pub block_number: BlockNumber,
+    pub extrinsics: Vec<ExtrinsicReceipt<Balance, Event>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExtrinsicReceipt<Balance, Event> {
+    pub result: DispatchResult,
+    pub weight: Weight,
+    pub fee: Balance,
+    pub events: Vec<Event>,
+}
+
+/// Describes a pallet and all of its calls.
+#[derive(Debug)]
+pub struct PalletMetadata {
+    pub name: &'static str,
+    pub calls: Vec<CallMetadata>,
+}
+
+/// Describes a callable function of a pallet. The `caller` is not included in the arguments.
+#[derive(Debug)]
+pub struct CallMetadata {
+    pub name: &'static str,
+    pub args: Vec<ArgMetadata>,
+}
+
+#[derive(Debug)]
+pub struct ArgMetadata {
+    pub name: &'static str,
+    /// The type of the argument as written in the pallet, e.g. `T::AccountId`.
+    pub type_name: &'static str,
+}
+
+impl<H: ToJson, E: ToJson> ToJson for Block<H, E> {
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("header", self.header.to_json()),
+            ("extrinsics", self.extrinsics.to_json()),
+        ])
+    }
+}
+
+impl<H: FromJson, E: FromJson> FromJson for Block<H, E> {
+    fn from_json(value: &Value) -> Result<Self, &'static str> {
+        Ok(Block {
+            header: H::from_json(value.field("header")?)?,
+            extrinsics: Vec::from_json(value.field("extrinsics")?)?,
+        })
+    }
+}
+
+impl<B: ToJson> ToJson for Header<B> {
+    fn to_json(&self) -> Value {
+        Value::object([("block_number", self.block_number.to_json())])
+    }
+}
+
+impl<B: FromJson> FromJson for Header<B> {
+    fn from_json(value: &Value) -> Result<Self, &'static str> {
+        Ok(Header {
+            block_number: B::from_json(value.field("block_number")?)?,
+        })
+    }
+}
+
+impl<Caller: ToJson, Call: ToJson> ToJson for Extrinsic<Caller, Call> {
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("caller", self.caller.to_json()),
+            ("call", self.call.to_json()),
+        ])
+    }
+}
+
+impl<Caller: FromJson, Call: FromJson> FromJson for Extrinsic<Caller, Call> {
+    fn from_json(value: &Value) -> Result<Self, &'static str> {
+        Ok(Extrinsic {
+            caller: Caller::from_json(value.field("caller")?)?,
+            call: Call::from_json(value.field("call")?)?,
+        })
+    }
+}
+
+impl<B: ToJson, Balance: ToJson, Event: ToJson> ToJson for BlockReceipt<B, Balance, Event> {
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("block_number", self.block_number.to_json()),
+            ("extrinsics", self.extrinsics.to_json()),
+        ])
+    }
+}
+
+impl<Balance: ToJson, Event: ToJson> ToJson for ExtrinsicReceipt<Balance, Event> {
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("success", self.result.is_ok().to_json()),
+            ("error", self.result.err().to_json()),
+            ("weight", self.weight.to_json()),
+            ("fee", self.fee.to_json()),
+            ("events", self.events.to_json()),
+        ])
+    }
+}
@@ -1,17 +1,31 @@
 use num::{One, Zero};
 use std::collections::BTreeMap;
+use std::fmt::Debug;
 use std::ops::AddAssign;
 
-pub trait Config {
-    type AccountId: Ord + Clone;
-    type BlockNumber: Zero + One + Copy + AddAssign;
+use crate::support::{DispatchResult, StorageSize};
+
+pub trait Config: Sized {
+    type AccountId: Debug + Ord + Clone;
+    type BlockNumber: Debug + Zero + One + Copy + AddAssign;
     type Nonce: Zero + One + Copy;
+    type RuntimeEvent: Clone;
+}
+
+/// An event, along with the index of the extrinsic in the block which emitted it.
+#[derive(Debug, Clone)]
+pub struct EventRecord<Event> {
+    pub extrinsic_index: u32,
+    pub event: Event,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Pallet<T: Config> {
     block_number: T::BlockNumber,
     nonce: BTreeMap<T::AccountId, T::Nonce>,
+    /// The account allowed to make privileged calls, set in the genesis state.
+    root: Option<T::AccountId>,
+    events: Vec<EventRecord<T::RuntimeEvent>>,
 }
 
 impl<T: Config> Pallet<T> {
@@ -19,6 +33,8 @@ impl<T: Config> Pallet<T> {
         Self {
             block_number: T::BlockNumber::zero(),
             nonce: BTreeMap::new(),
+            root: None,
+            events: Vec::new(),
         }
     }
 
@@ -39,6 +55,49 @@ impl<T: Config> Pallet<T> {
 
         self.nonce.insert(who.clone(), nonce + T::Nonce::one());
     }
+
+    /// Forget an account which no longer exists, resetting its nonce.
+    pub fn remove_account(&mut self, who: &T::AccountId) {
+        self.nonce.remove(who);
+    }
+
+    pub fn root(&self) -> Option<&T::AccountId> {
+        self.root.as_ref()
+    }
+
+    pub fn set_root(&mut self, root: Option<T::AccountId>) {
+        self.root = root;
+    }
+
+    /// Check that `who` is the root account, for calls only root can make.
+    pub fn ensure_root(&self, who: &T::AccountId) -> DispatchResult {
+        if self.root.as_ref() != Some(who) {
+            return Err("Caller is not root");
+        }
+        Ok(())
+    }
+
+    /// The events emitted in the current block.
+    pub fn events(&self) -> &[EventRecord<T::RuntimeEvent>] {
+        &self.events
+    }
+
+    pub fn deposit_event(&mut self, extrinsic_index: u32, event: T::RuntimeEvent) {
+        self.events.push(EventRecord {
+            extrinsic_index,
+            event,
+        });
+    }
+
+    pub fn reset_events(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl<T: Config> StorageSize for Pallet<T> {
+    fn storage_size(&self) -> usize {
+        self.nonce.len() + self.events.len()
+    }
 }
 
 #[cfg(test)]
@@ -48,11 +107,12 @@ mod tests {
         type AccountId = String;
         type BlockNumber = u32;
         type Nonce = u32;
+        type RuntimeEvent = &'static str;
     }
 
     #[test]
     fn init_system() {
-        let mut system: super::Pallet<TestConfig> = super::Pallet::new();
+        let system: super::Pallet<TestConfig> = super::Pallet::new();
 
         assert_eq!(system.block_number(), 0);
     }
@@ -75,4 +135,32 @@ mod tests {
         system.inc_nonce(&"Alice".to_string());
         assert_eq!(system.nonce(&"Alice".to_string()), 1);
     }
+
+    #[test]
+    fn ensure_root() {
+        let mut system: super::Pallet<TestConfig> = super::Pallet::new();
+        let alice = "Alice".to_string();
+
+        assert_eq!(system.ensure_root(&alice), Err("Caller is not root"));
+        system.set_root(Some(alice.clone()));
+        assert_eq!(system.ensure_root(&alice), Ok(()));
+        assert_eq!(
+            system.ensure_root(&"Bob".to_string()),
+            Err("Caller is not root")
+        );
+    }
+
+    #[test]
+    fn deposit_events() {
+        let mut system: super::Pallet<TestConfig> = super::Pallet::new();
+
+        system.deposit_event(0, "first");
+        system.deposit_event(1, "second");
+        assert_eq!(system.events().len(), 2);
+        assert_eq!(system.events()[1].extrinsic_index, 1);
+        assert_eq!(system.events()[1].event, "second");
+
+        system.reset_events();
+        assert!(system.events().is_empty());
+    }
 }
@@ -0,0 +1,263 @@
+use crate::support::{
+    ChargeFee, Dispatch, DispatchError, DispatchResult, GetWeight, Hooks, StorageSize, Weight,
+};
+
+pub trait Config: crate::system::Config {
+    type RuntimeCall: GetWeight;
+    /// Batches are dispatched through the runtime, and `batch_all` restores a copy of it when one
+    /// of its calls fails. The caller is charged for the copy according to the size of the state.
+    type Runtime: AsMut<Pallet<Self>>
+        + Clone
+        + StorageSize
+        + ChargeFee<Caller = Self::AccountId>
+        + Dispatch<Caller = Self::AccountId, Call = Self::RuntimeCall>;
+}
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+    events: Vec<Event<T>>,
+}
+
+#[macros::event]
+pub enum Event<T: Config> {
+    ItemCompleted {
+        index: u32,
+    },
+    ItemFailed {
+        index: u32,
+        error: DispatchError,
+    },
+    /// A `batch` stopped at the call `index`, the calls before it were dispatched.
+    BatchInterrupted {
+        index: u32,
+        error: DispatchError,
+    },
+    BatchCompleted {
+        who: T::AccountId,
+        count: u32,
+    },
+    BatchCompletedWithErrors {
+        who: T::AccountId,
+        count: u32,
+        failed: u32,
+    },
+}
+
+/// The number of storage items `batch_all` copies for each unit of weight it charges.
+const SNAPSHOT_ITEMS_PER_WEIGHT: usize = 10;
+
+/// The weight of copying a state of `storage_size` items.
+fn snapshot_weight(storage_size: usize) -> Weight {
+    storage_size.div_ceil(SNAPSHOT_ITEMS_PER_WEIGHT) as Weight
+}
+
+/// The weight of a batch is the weight of all of its calls.
+fn batch_weight<Call: GetWeight>(calls: &[Call]) -> Weight {
+    calls.iter().map(GetWeight::weight).sum::<Weight>() + crate::support::DEFAULT_WEIGHT
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Dispatch `calls` as the caller, stopping at the first one which fails. The calls before it
+    /// are not reverted.
+    #[weight(batch_weight(calls))]
+    pub fn batch(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        calls: Vec<T::RuntimeCall>,
+    ) -> DispatchResult {
+        let count = calls.len() as u32;
+        for (index, call) in calls.into_iter().enumerate() {
+            let index = index as u32;
+            if let Err(error) = runtime.dispatch(caller.clone(), call) {
+                Self::deposit_event(runtime, Event::BatchInterrupted { index, error });
+                return Ok(());
+            }
+            Self::deposit_event(runtime, Event::ItemCompleted { index });
+        }
+        Self::deposit_event(runtime, Event::BatchCompleted { who: caller, count });
+        Ok(())
+    }
+
+    /// Dispatch `calls` as the caller, reverting all of them if one fails.
+    ///
+    /// The whole runtime is copied first so that it can be restored. Since the cost of the copy
+    /// depends on the state, it is charged here rather than in the weight of the call, and is not
+    /// refunded if the batch fails.
+    #[weight(batch_weight(calls))]
+    pub fn batch_all(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        calls: Vec<T::RuntimeCall>,
+    ) -> DispatchResult {
+        runtime.charge_fee(&caller, snapshot_weight(runtime.storage_size()))?;
+        let snapshot = runtime.clone();
+        let count = calls.len() as u32;
+        for (index, call) in calls.into_iter().enumerate() {
+            let index = index as u32;
+            if let Err(error) = runtime.dispatch(caller.clone(), call) {
+                *runtime = snapshot;
+                return Err(error);
+            }
+            Self::deposit_event(runtime, Event::ItemCompleted { index });
+        }
+        Self::deposit_event(runtime, Event::BatchCompleted { who: caller, count });
+        Ok(())
+    }
+
+    /// Dispatch `calls` as the caller, carrying on past the ones which fail.
+    #[weight(batch_weight(calls))]
+    pub fn force_batch(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        calls: Vec<T::RuntimeCall>,
+    ) -> DispatchResult {
+        let count = calls.len() as u32;
+        let mut failed = 0;
+        for (index, call) in calls.into_iter().enumerate() {
+            let index = index as u32;
+            match runtime.dispatch(caller.clone(), call) {
+                Ok(()) => Self::deposit_event(runtime, Event::ItemCompleted { index }),
+                Err(error) => {
+                    failed += 1;
+                    Self::deposit_event(runtime, Event::ItemFailed { index, error });
+                }
+            }
+        }
+        let event = if failed == 0 {
+            Event::BatchCompleted { who: caller, count }
+        } else {
+            Event::BatchCompletedWithErrors {
+                who: caller,
+                count,
+                failed,
+            }
+        };
+        Self::deposit_event(runtime, event);
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Self { events: Vec::new() }
+    }
+
+    fn deposit_event(runtime: &mut T::Runtime, event: Event<T>) {
+        let pallet: &mut Self = runtime.as_mut();
+        pallet.events.push(event);
+    }
+
+    pub fn take_events(&mut self) -> Vec<Event<T>> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl<T: Config> StorageSize for Pallet<T> {
+    fn storage_size(&self) -> usize {
+        0
+    }
+}
+
+impl<T: Config, Runtime> Hooks<Runtime> for Pallet<T> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::chain_spec::ChainSpec;
+    use crate::support::{GetWeight, StorageSize};
+    use crate::{Runtime, RuntimeCall, balances, proof_of_existence};
+
+    type Utility = super::Pallet<Runtime>;
+    type PoE = proof_of_existence::Pallet<Runtime>;
+
+    fn transfer(to: &str, amount: u128) -> RuntimeCall {
+        RuntimeCall::balances(balances::Call::transfer {
+            to: to.to_string(),
+            amount,
+        })
+    }
+
+    fn claim(claim: &str) -> RuntimeCall {
+        RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+            claim: PoE::hash_bytes(claim.as_bytes()),
+            description: None,
+            timestamp: None,
+            expires_at: None,
+        })
+    }
+
+    #[test]
+    fn batch_stops_at_first_error() {
+        let mut runtime = ChainSpec::development().build_runtime();
+        let alice = "Alice".to_string();
+
+        let calls = vec![
+            transfer("Bob", 10),
+            transfer("Bob", 1000),
+            transfer("Bob", 10),
+        ];
+        Utility::batch(&mut runtime, alice.clone(), calls).unwrap();
+        assert_eq!(runtime.balances.balance(&"Bob".to_string()), 10);
+        assert_eq!(runtime.utility.events.len(), 2);
+    }
+
+    #[test]
+    fn batch_all_is_atomic() {
+        let mut runtime = ChainSpec::development().build_runtime();
+        let alice = "Alice".to_string();
+
+        let calls = vec![transfer("Bob", 10), claim("doc"), claim("doc")];
+        let call = RuntimeCall::utility(super::Call::batch_all {
+            calls: calls.clone(),
+        });
+        assert_eq!(call.weight(), 2 + 1 + 1 + 1);
+        // Only Alice has an account, so copying the state costs a single unit of weight, which
+        // is kept when the batch fails.
+        assert_eq!(runtime.storage_size(), 1);
+        assert_eq!(super::snapshot_weight(runtime.storage_size()), 1);
+        assert_eq!(super::snapshot_weight(25), 3);
+        let res = Utility::batch_all(&mut runtime, alice.clone(), calls);
+        assert_eq!(res, Err("Claim already exists"));
+        assert_eq!(runtime.balances.balance(&alice), 99);
+        assert_eq!(
+            runtime
+                .proof_of_existence
+                .get_claim(&PoE::hash_bytes(b"doc")),
+            None
+        );
+        assert!(runtime.balances.take_events().is_empty());
+
+        let calls = vec![transfer("Bob", 10), claim("doc")];
+        Utility::batch_all(&mut runtime, alice.clone(), calls).unwrap();
+        // The copy of the state, the transfer, and the deposit of the claim.
+        assert_eq!(runtime.balances.balance(&alice), 87);
+        assert!(
+            runtime
+                .proof_of_existence
+                .get_claim(&PoE::hash_bytes(b"doc"))
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn force_batch_continues_past_errors() {
+        let mut runtime = ChainSpec::development().build_runtime();
+        let alice = "Alice".to_string();
+
+        let calls = vec![transfer("Bob", 1000), transfer("Bob", 10), claim("doc")];
+        let call = RuntimeCall::utility(super::Call::force_batch {
+            calls: calls.clone(),
+        });
+        assert_eq!(call.weight(), 2 + 2 + 1 + 1);
+
+        Utility::force_batch(&mut runtime, alice.clone(), calls).unwrap();
+        assert_eq!(runtime.balances.balance(&"Bob".to_string()), 10);
+        assert!(
+            runtime
+                .proof_of_existence
+                .get_claim(&PoE::hash_bytes(b"doc"))
+                .is_some()
+        );
+        assert_eq!(runtime.utility.events.len(), 4);
+    }
+}
@@ -0,0 +1,298 @@
+use num::{CheckedAdd, CheckedMul, CheckedSub, Zero};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use crate::json::{FromJson, ToJson, Value};
+use crate::support::{
+    Currency, DispatchResult, ExistenceRequirement, Hooks, LockIdentifier, LockableCurrency,
+    StorageSize, WithdrawReasons,
+};
+use crate::system;
+
+const VESTING_ID: LockIdentifier = *b"vesting ";
+
+/// The most schedules an account can have at once.
+const MAX_SCHEDULES: usize = 8;
+
+pub trait Config: crate::system::Config {
+    type Balance: Debug
+        + Zero
+        + CheckedAdd
+        + CheckedSub
+        + CheckedMul
+        + Ord
+        + Copy
+        + From<Self::BlockNumber>;
+    type Currency: LockableCurrency<Self::AccountId, Balance = Self::Balance>;
+    /// The block number is read from the system pallet, and the vested funds are locked in the
+    /// currency.
+    type Runtime: AsMut<Pallet<Self>> + AsMut<system::Pallet<Self>> + AsMut<Self::Currency>;
+}
+
+/// Funds which unlock linearly: `locked` is frozen until `starting_block`, then `per_block` of it
+/// unlocks with every block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VestingSchedule<Balance, BlockNumber> {
+    pub locked: Balance,
+    pub per_block: Balance,
+    pub starting_block: BlockNumber,
+}
+
+pub type ScheduleOf<T> =
+    VestingSchedule<<T as Config>::Balance, <T as system::Config>::BlockNumber>;
+
+#[derive(Debug, Clone)]
+pub struct Pallet<T: Config> {
+    vesting: BTreeMap<T::AccountId, Vec<ScheduleOf<T>>>,
+    events: Vec<Event<T>>,
+}
+
+#[macros::event]
+pub enum Event<T: Config> {
+    VestedTransfer {
+        who: T::AccountId,
+        locked: T::Balance,
+    },
+    VestingUpdated {
+        who: T::AccountId,
+        unvested: T::Balance,
+    },
+    VestingCompleted {
+        who: T::AccountId,
+    },
+}
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Transfer `schedule.locked` to `target`, where it stays locked until it vests.
+    #[weight(3)]
+    pub fn vested_transfer(
+        runtime: &mut T::Runtime,
+        caller: T::AccountId,
+        target: T::AccountId,
+        schedule: ScheduleOf<T>,
+    ) -> DispatchResult {
+        if schedule.locked.is_zero() || schedule.per_block.is_zero() {
+            return Err("Invalid vesting schedule");
+        }
+        let pallet: &mut Self = runtime.as_mut();
+        if pallet.schedules(&target).len() >= MAX_SCHEDULES {
+            return Err("Too many vesting schedules");
+        }
+
+        let currency: &mut T::Currency = runtime.as_mut();
+        currency.transfer(
+            &caller,
+            &target,
+            schedule.locked,
+            ExistenceRequirement::AllowDeath,
+        )?;
+
+        let locked = schedule.locked;
+        let pallet: &mut Self = runtime.as_mut();
+        pallet
+            .vesting
+            .entry(target.clone())
+            .or_default()
+            .push(schedule);
+        pallet.deposit_event(Event::VestedTransfer {
+            who: target.clone(),
+            locked,
+        });
+        Self::update_lock(runtime, &target);
+        Ok(())
+    }
+
+    /// Unlock the funds of the caller which have vested so far.
+    pub fn vest(runtime: &mut T::Runtime, caller: T::AccountId) -> DispatchResult {
+        let pallet: &mut Self = runtime.as_mut();
+        if pallet.schedules(&caller).is_empty() {
+            return Err("Account has no vesting schedule");
+        }
+        Self::update_lock(runtime, &caller);
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Self {
+            vesting: BTreeMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn schedules(&self, who: &T::AccountId) -> &[ScheduleOf<T>] {
+        self.vesting.get(who).map_or(&[], Vec::as_slice)
+    }
+
+    /// The funds of `who` which have not vested yet at block `now`.
+    pub fn unvested(&self, who: &T::AccountId, now: T::BlockNumber) -> T::Balance {
+        self.schedules(who)
+            .iter()
+            .map(|schedule| Self::locked_at(schedule, now))
+            // Every schedule was funded, so their sum is part of the total issuance.
+            .fold(T::Balance::zero(), |total, locked| total + locked)
+    }
+
+    fn locked_at(schedule: &ScheduleOf<T>, now: T::BlockNumber) -> T::Balance {
+        let now = T::Balance::from(now);
+        let start = T::Balance::from(schedule.starting_block);
+        if now <= start {
+            return schedule.locked;
+        }
+        (now - start)
+            .checked_mul(&schedule.per_block)
+            .and_then(|vested| schedule.locked.checked_sub(&vested))
+            .unwrap_or(T::Balance::zero())
+    }
+
+    /// Set the vesting lock of `who` to what has not vested yet, and forget the schedules which
+    /// have fully vested.
+    fn update_lock(runtime: &mut T::Runtime, who: &T::AccountId) {
+        let system: &mut system::Pallet<T> = runtime.as_mut();
+        let now = system.block_number();
+
+        let pallet: &mut Self = runtime.as_mut();
+        let unvested = pallet.unvested(who, now);
+        let schedules = pallet.vesting.entry(who.clone()).or_default();
+        schedules.retain(|schedule| !Self::locked_at(schedule, now).is_zero());
+        if schedules.is_empty() {
+            pallet.vesting.remove(who);
+            pallet.deposit_event(Event::VestingCompleted { who: who.clone() });
+        } else {
+            pallet.deposit_event(Event::VestingUpdated {
+                who: who.clone(),
+                unvested,
+            });
+        }
+
+        let currency: &mut T::Currency = runtime.as_mut();
+        if unvested.is_zero() {
+            currency.remove_lock(VESTING_ID, who);
+        } else {
+            currency.set_lock(VESTING_ID, who, unvested, WithdrawReasons::All);
+        }
+    }
+
+    fn deposit_event(&mut self, event: Event<T>) {
+        self.events.push(event);
+    }
+
+    pub fn take_events(&mut self) -> Vec<Event<T>> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl<T: Config> StorageSize for Pallet<T> {
+    fn storage_size(&self) -> usize {
+        self.vesting.values().map(Vec::len).sum()
+    }
+}
+
+impl<T: Config, Runtime> Hooks<Runtime> for Pallet<T> {}
+
+impl<Balance: ToJson, BlockNumber: ToJson> ToJson for VestingSchedule<Balance, BlockNumber> {
+    fn to_json(&self) -> Value {
+        Value::object([
+            ("locked", self.locked.to_json()),
+            ("per_block", self.per_block.to_json()),
+            ("starting_block", self.starting_block.to_json()),
+        ])
+    }
+}
+
+impl<Balance: FromJson, BlockNumber: FromJson> FromJson for VestingSchedule<Balance, BlockNumber> {
+    fn from_json(value: &Value) -> Result<Self, &'static str> {
+        Ok(Self {
+            locked: Balance::from_json(value.field("locked")?)?,
+            per_block: Balance::from_json(value.field("per_block")?)?,
+            starting_block: BlockNumber::from_json(value.field("starting_block")?)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VestingSchedule;
+    use crate::Runtime;
+    use crate::chain_spec::ChainSpec;
+
+    type Vesting = super::Pallet<Runtime>;
+
+    #[test]
+    fn vest_linearly() {
+        let mut runtime = ChainSpec::development().build_runtime();
+        let alice = "Alice".to_string();
+        let bob = "Bob".to_string();
+        let charlie = "Charlie".to_string();
+
+        let schedule = VestingSchedule {
+            locked: 50,
+            per_block: 10,
+            starting_block: 2,
+        };
+        Vesting::vested_transfer(&mut runtime, alice.clone(), bob.clone(), schedule).unwrap();
+        let schedule = VestingSchedule {
+            locked: 20,
+            per_block: 5,
+            starting_block: 0,
+        };
+        Vesting::vested_transfer(&mut runtime, alice.clone(), bob.clone(), schedule).unwrap();
+        assert_eq!(runtime.balances.balance(&bob), 70);
+
+        // Nothing has vested yet.
+        let res = runtime.balances.transfer(bob.clone(), charlie.clone(), 10);
+        assert_eq!(res, Err("Balance is locked"));
+
+        // At block 3, 10 of the first schedule and 15 of the second have vested.
+        for _ in 0..3 {
+            runtime.system.inc_block_number();
+        }
+        assert_eq!(runtime.vesting.unvested(&bob, 3), 45);
+        // The lock is only lowered by `vest`.
+        let res = runtime.balances.transfer(bob.clone(), charlie.clone(), 10);
+        assert_eq!(res, Err("Balance is locked"));
+        Vesting::vest(&mut runtime, bob.clone()).unwrap();
+        runtime
+            .balances
+            .transfer(bob.clone(), charlie.clone(), 25)
+            .unwrap();
+        let res = runtime.balances.transfer(bob.clone(), charlie.clone(), 1);
+        assert_eq!(res, Err("Balance is locked"));
+
+        // The second schedule is done at block 4, the first at block 7.
+        for _ in 0..4 {
+            runtime.system.inc_block_number();
+        }
+        Vesting::vest(&mut runtime, bob.clone()).unwrap();
+        assert!(runtime.vesting.schedules(&bob).is_empty());
+        assert!(runtime.balances.locks(&bob).is_empty());
+        let res = Vesting::vest(&mut runtime, bob.clone());
+        assert_eq!(res, Err("Account has no vesting schedule"));
+    }
+
+    #[test]
+    fn invalid_vesting_schedule() {
+        let mut runtime = ChainSpec::development().build_runtime();
+        let alice = "Alice".to_string();
+        let bob = "Bob".to_string();
+
+        let schedule = VestingSchedule {
+            locked: 50,
+            per_block: 0,
+            starting_block: 0,
+        };
+        let res = Vesting::vested_transfer(&mut runtime, alice.clone(), bob.clone(), schedule);
+        assert_eq!(res, Err("Invalid vesting schedule"));
+
+        let schedule = VestingSchedule {
+            locked: 500,
+            per_block: 10,
+            starting_block: 0,
+        };
+        let res = Vesting::vested_transfer(&mut runtime, alice.clone(), bob.clone(), schedule);
+        assert_eq!(res, Err("Insufficient balance"));
+        assert!(runtime.vesting.schedules(&bob).is_empty());
+    }
+}